@@ -1,8 +1,6 @@
 use siafu::{JobBuilder, Scheduler};
 use std::time::Duration;
-use std::time::SystemTime;
 use std::sync::{Arc, Mutex};
-use std::thread;
 use std::collections::HashMap;
 
 // Import rand for the random boolean generation
@@ -12,6 +10,7 @@ use siafu::utils::time::ScheduleTime;
 use siafu::scheduler::types::RecurringInterval;
 
 // Shared state to simulate job dependencies and error tracking
+#[allow(dead_code)]
 struct AppState {
     job_results: HashMap<String, bool>,
     error_count: HashMap<String, u32>,
@@ -67,28 +66,29 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .add_handler(extract_job_handler)
         .build();
     
-    let extract_state = Arc::clone(&state);
-    let extract_job_id = extract_job.name.clone();
+    let _extract_state = Arc::clone(&state);
+    let extract_job_id = extract_job.id;
     scheduler.add_job(extract_job)?;
     
-    // Step 2: Transform job (depends on extract)
+    // Step 2: Transform job (only runs once extract has completed)
     let transform_job = JobBuilder::new("transform-data")
-        .once(ScheduleTime::Delay(Duration::from_secs(8)))
+        .once(ScheduleTime::Delay(Duration::from_secs(3)))
+        .after(extract_job_id)
         .add_handler(transform_job_handler)
         .build();
     
-    let transform_state = Arc::clone(&state);
-    let transform_job_id = transform_job.name.clone();
+    let _transform_state = Arc::clone(&state);
+    let transform_job_id = transform_job.id;
     scheduler.add_job(transform_job)?;
     
-    // Step 3: Load job (depends on transform)
+    // Step 3: Load job (only runs once transform has completed)
     let load_job = JobBuilder::new("load-data")
-        .once(ScheduleTime::Delay(Duration::from_secs(13)))
+        .once(ScheduleTime::Delay(Duration::from_secs(3)))
+        .after(transform_job_id)
         .add_handler(load_job_handler)
         .build();
     
-    let load_state = Arc::clone(&state);
-    let load_job_id = load_job.name.clone();
+    let _load_state = Arc::clone(&state);
     scheduler.add_job(load_job)?;
     
     // Monitoring job that runs every 5 seconds
@@ -97,7 +97,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .add_handler(monitor_job_handler)
         .build();
     
-    let monitor_state = Arc::clone(&state);
+    let _monitor_state = Arc::clone(&state);
     scheduler.add_job(monitor_job)?;
     
     println!("🚀 Advanced job orchestration system started");
@@ -0,0 +1,14 @@
+//! Feeds arbitrary strings to `ScheduleTime::from_str`, which must reject them without
+//! panicking. Run with `cargo +nightly fuzz run schedule_time` from the repository root.
+
+#![no_main]
+
+use std::str::FromStr;
+use libfuzzer_sys::fuzz_target;
+use siafu::ScheduleTime;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let _ = ScheduleTime::from_str(input);
+    }
+});
@@ -1,6 +1,6 @@
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Error {
     InvalidSchedule(String),
     JobNotFound(String),
@@ -8,6 +8,8 @@ pub enum Error {
     HandlerNotBuilt,
     MissingSchedule,
     TimeCalculationError,
+    Io(String),
+    Serialization(String),
 }
 
 impl std::error::Error for Error {}
@@ -21,6 +23,8 @@ impl fmt::Display for Error {
             Error::HandlerNotBuilt => write!(f, "Handler not built!"),
             Error::MissingSchedule => write!(f, "No schedule found!"),
             Error::TimeCalculationError => write!(f, "Error calculating target time"),
+            Error::Io(msg) => write!(f, "I/O error: {}", msg),
+            Error::Serialization(msg) => write!(f, "Serialization error: {}", msg),
         }
     }
 }
@@ -30,4 +34,25 @@ impl From<crate::utils::time::ScheduleTimeError> for Error {
     fn from(err: crate::utils::time::ScheduleTimeError) -> Self {
         Error::InvalidSchedule(err.to_string())
     }
-}
\ No newline at end of file
+}
+
+// Convert I/O failures (e.g. while persisting scheduler state) into the library Error
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err.to_string())
+    }
+}
+
+// Convert ScheduleSpecError into the library Error
+impl From<crate::utils::spec::ScheduleSpecError> for Error {
+    fn from(err: crate::utils::spec::ScheduleSpecError) -> Self {
+        Error::InvalidSchedule(err.to_string())
+    }
+}
+
+// Convert CronBuilderError into the library Error
+impl From<crate::utils::cron_builder::CronBuilderError> for Error {
+    fn from(err: crate::utils::cron_builder::CronBuilderError) -> Self {
+        Error::InvalidSchedule(err.to_string())
+    }
+}
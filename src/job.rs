@@ -1,7 +1,11 @@
-
 pub mod builder;
 pub mod base;
+pub mod types;
+pub mod template;
+pub mod retry;
 
 pub use builder::JobBuilder;
 pub use base::JobExecutor;
-
+pub use template::JobTemplate;
+pub use retry::{BackoffStrategy, RetryPolicy};
+pub use types::{Job, JobMeta, JobSnapshot, JobState, JobStats, RunOutcome, RunRecord, ScheduleStatus};
@@ -1,6 +1,6 @@
 //! JobExecutor defines the interface for executing scheduled jobs.
 //!
-//! Implementors should run the job logic in `run` and may override `get_next_run`
+//! Implementors should run the job logic in `run` or `run_at` and may override `get_next_run`
 
 use std::time::SystemTime;
 use crate::error::Error as JobSchedulerError;
@@ -12,6 +12,14 @@ pub trait JobExecutor {
     /// Returns `Ok(())` on success, or an `Error` if execution fails or handler is missing.
     fn run(&mut self) -> Result<(), JobSchedulerError>;
 
+    /// Execute the job's handler, treating `now` as the current time.
+    ///
+    /// Lets tests run a job against a controlled clock. Defaults to `run`, ignoring `now`.
+    fn run_at(&mut self, now: SystemTime) -> Result<(), JobSchedulerError> {
+        let _ = now;
+        self.run()
+    }
+
     /// Optionally return the next scheduled run time for this job.
     ///
     /// Default implementation returns `None`.
@@ -1,5 +1,5 @@
 //! JobBuilder provides a fluent API to configure scheduled jobs with various types (once, recurring, cron, random),
-//! set maximum repeats, and assign execution handlers.
+//! set maximum repeats, and assign execution handlers. `build` finalizes the configuration into a `Job`.
 //!
 //! # Examples
 //!
@@ -28,59 +28,138 @@
 //!     .build();
 //! ```
 
+use std::any::Any;
+use std::sync::Arc;
 use std::time::{SystemTime, Duration};
-use crate::scheduler::types::{Schedule, ScheduleType, RandomSchedule, RecurringSchedule, RecurringInterval};
+use crate::scheduler::types::{Schedule, ScheduleType, AfterJobSchedule, DailyTimesSchedule, DynamicSchedule, OpportunisticSchedule, BusinessDayCronSchedule, Distribution, RandomSchedule, RandomWindowSchedule, RecurringSchedule, RecurringInterval, NthCronSchedule, add_years, RunOffset, TimeWindow, ZonedCronSchedule, DstPolicy, midnight_utc};
 use uuid::Uuid;
 use crate::error::Error as JobSchedulerError;
-use super::JobExecutor;
-use chrono::Utc;
-use rand::{rng, Rng};
+use super::types::{Job, JobCompleteHandler, JobErrorHandler, JobGuard, JobHandler, JobKey, JobMeta, JobResults};
+use super::retry::{BackoffStrategy, RetryPolicy};
+use chrono::{Datelike, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use rand::rngs::StdRng;
+use rand::{rng, Rng, SeedableRng};
 use cron::Schedule as CronSchedule;
-use crate::utils::time::ScheduleTime;
+use crate::utils::time::{ScheduleTime, random_time_between};
+use crate::utils::spec::ScheduleSpec;
+use crate::utils::cron_builder::validate_cron;
 use std::str::FromStr;
 
-// Define the handler type alias
-type JobHandler = Box<dyn Fn() + Send + 'static>;
-
 pub struct JobBuilder {
     pub id: Uuid,
     pub name: Option<String>,
     pub schedules: Vec<Schedule>,
-    pub last_run: Option<SystemTime>,
-    pub next_run: Option<SystemTime>,
     pub handler: Option<JobHandler>,
+    pub context: Option<Box<dyn Any + Send>>,
+    pub dependencies: Vec<Uuid>,
+    pub error_handler: Option<JobErrorHandler>,
+    pub complete_handler: Option<JobCompleteHandler>,
+    pub guard: Option<JobGuard>,
+    pub offset: Option<RunOffset>,
+    pub dedicated_thread: bool,
+    pub coalesce: bool,
+    pub priority: i32,
+    pub active_window: Option<TimeWindow>,
+    pub retry: Option<RetryPolicy>,
+    pub skip_first: bool,
+    pub run_on_start: bool,
+    pub idempotency_key: Option<JobKey>,
+    // First error hit while configuring, reported by `try_build` and `add_job`
+    pub(crate) build_error: Option<JobSchedulerError>,
+    // Filled by an `add_handler_returning` handler
+    pub(crate) results: JobResults,
 }
 
 impl JobBuilder {
-    /// Construct a new JobBuilder with optional name.
+    /// Construct a new JobBuilder with the given name.
+    ///
+    /// The name is kept exactly as given, so `new("")` names the job `Some("")`. Use
+    /// `new_unnamed` for a job without a name.
     pub fn new(name: &str) -> Self {
+        let mut builder = Self::new_unnamed();
+        builder.name = Some(name.to_string());
+        builder
+    }
+
+    /// Construct a new JobBuilder for a job without a name.
+    pub fn new_unnamed() -> Self {
         Self {
             id: Uuid::new_v4(),
-            name: if name.is_empty() { None } else { Some(name.to_string()) },
+            name: None,
             schedules: Vec::new(),
-            last_run: None,
-            next_run: None,
             handler: None,
+            context: None,
+            dependencies: Vec::new(),
+            error_handler: None,
+            complete_handler: None,
+            guard: None,
+            offset: None,
+            dedicated_thread: false,
+            coalesce: false,
+            priority: 0,
+            active_window: None,
+            retry: None,
+            skip_first: false,
+            run_on_start: false,
+            idempotency_key: None,
+            build_error: None,
+            results: JobResults::default(),
         }
     }
 
+    /// Use a fixed id instead of a randomly generated one.
+    ///
+    /// A stable id lets saved state and external systems refer to the job across restarts.
+    pub fn id(mut self, id: Uuid) -> Self {
+        self.id = id;
+        self
+    }
+
     /// Schedule the job to run once at the specified time.
     /// 
     /// Takes a ScheduleTime which can be either a specific time (At) or a delay (Delay).
+    /// A delay too large to represent is not scheduled; `try_build` and `Scheduler::add_job`
+    /// then fail with `TimeCalculationError`.
     pub fn once(mut self, time: ScheduleTime) -> Self {
-        match time {
-            ScheduleTime::At(system_time) => {
-                let sched = Schedule { schedule_type: ScheduleType::Once(system_time), max_runs: Some(1), run_count: 0 };
-                self.next_run = self.next_run.map_or(Some(system_time), |nr| Some(nr.min(system_time)));
-                self.schedules.push(sched);
-            },
-            ScheduleTime::Delay(duration) => {
-                let system_time = SystemTime::now() + duration;
-                let sched = Schedule { schedule_type: ScheduleType::Once(system_time), max_runs: Some(1), run_count: 0 };
-                self.next_run = self.next_run.map_or(Some(system_time), |nr| Some(nr.min(system_time)));
-                self.schedules.push(sched);
-            }
-        }
+        let Some(system_time) = self.resolve_time(&time) else {
+            return self;
+        };
+        let sched = Schedule { schedule_type: ScheduleType::Once(system_time), max_runs: Some(1), run_count: 0, next_run: Some(system_time) };
+        self.push_schedule(sched);
+        self
+    }
+
+    /// Schedule the job to run once, on the very next sweep.
+    ///
+    /// Unlike `once(ScheduleTime::At(SystemTime::now()))`, the run does not depend on the
+    /// sweep's clock reading being past the build time, so it fires even if the clock steps
+    /// back a little. Its `next_run` reports the build time.
+    pub fn now_once(mut self) -> Self {
+        self.push_schedule(immediate_schedule());
+        self
+    }
+
+    /// Schedule the job with custom logic that picks each next run.
+    ///
+    /// The first run is the schedule's first time after now.
+    pub fn dynamic<S: DynamicSchedule + 'static>(mut self, schedule: S) -> Self {
+        let first = schedule.next_after(SystemTime::now());
+        let sched = Schedule { schedule_type: ScheduleType::Dynamic(Arc::new(schedule)), max_runs: None, run_count: 0, next_run: first };
+        self.push_schedule(sched);
+        self
+    }
+
+    /// Schedule the job to run once before `deadline`, as soon as `load_check` returns true.
+    ///
+    /// Each sweep until the deadline asks `load_check` whether now is a good time, e.g. while
+    /// the system is lightly loaded. If it never says yes the job runs at the deadline anyway.
+    pub fn opportunistic<F>(mut self, deadline: SystemTime, load_check: F) -> Self
+    where F: Fn() -> bool + Send + Sync + 'static {
+        let opportunistic = OpportunisticSchedule { deadline, load_check: Arc::new(load_check) };
+        let first = SystemTime::now().min(deadline);
+        let sched = Schedule { schedule_type: ScheduleType::Opportunistic(opportunistic), max_runs: Some(1), run_count: 0, next_run: Some(first) };
+        self.push_schedule(sched);
         self
     }
 
@@ -88,35 +167,70 @@ impl JobBuilder {
     ///
     /// This method takes a RecurringInterval directly and an optional start time.
     pub fn recurring(mut self, interval: RecurringInterval, start_time: Option<ScheduleTime>) -> Self {
+        // Store equivalent intervals in one canonical form
+        let interval = interval.normalize();
         // Determine the first run time
-        let first_run = match start_time {
-            Some(ScheduleTime::At(time)) => time,
-            Some(ScheduleTime::Delay(delay)) => SystemTime::now() + delay,
-            None => {
-                // Default to a reasonable start time based on the interval type
+        let first_run = match (start_time, &interval) {
+            (Some(time), _) => self.resolve_time(&time),
+            // Years vary in length, so step by calendar year from today
+            (None, RecurringInterval::Yearly(years)) => {
                 let now = SystemTime::now();
-                match &interval {
-                    RecurringInterval::Secondly(secs) => now + Duration::from_secs(*secs as u64),
-                    RecurringInterval::Minutely(mins) => now + Duration::from_secs(*mins as u64 * 60),
-                    RecurringInterval::Hourly(hours) => now + Duration::from_secs(*hours as u64 * 3600),
-                    RecurringInterval::Daily(days) => now + Duration::from_secs(*days as u64 * 86400),
-                    RecurringInterval::Weekly(weeks) => now + Duration::from_secs(*weeks as u64 * 604800),
-                    RecurringInterval::Monthly(months) => now + Duration::from_secs(*months as u64 * 2592000), // Approximate 30 days
-                    RecurringInterval::Custom { .. } => now + Duration::from_secs(60), // Default to 1 minute
+                let first = add_years(now, *years, chrono::DateTime::<Utc>::from(now).day());
+                if first.is_none() {
+                    self.build_error.get_or_insert(JobSchedulerError::TimeCalculationError);
                 }
+                first
             }
+            (None, _) => {
+                // Default to a reasonable start time based on the interval type
+                let delay = match &interval {
+                    RecurringInterval::Custom { .. } => Duration::from_secs(60), // Default to 1 minute
+                    other => other.delta(),
+                };
+                self.resolve_time(&ScheduleTime::Delay(delay))
+            }
+        };
+        let Some(first_run) = first_run else {
+            return self;
         };
         
         // Create the recurring schedule
-        let recurring = RecurringSchedule {
-            interval,
-            next_run: first_run,
-        };
+        let recurring = RecurringSchedule::new(interval, first_run);
         
         // Add to schedules
-        let sched = Schedule { schedule_type: ScheduleType::Recurring(recurring.clone()), max_runs: None, run_count: 0 };
-        self.next_run = self.next_run.map_or(Some(first_run), |nr| Some(nr.min(first_run)));
-        self.schedules.push(sched);
+        let sched = Schedule { schedule_type: ScheduleType::Recurring(recurring.clone()), max_runs: None, run_count: 0, next_run: Some(first_run) };
+        self.push_schedule(sched);
+        self
+    }
+
+    /// Schedule the job with a recurring interval measured from the end of the previous run.
+    ///
+    /// `recurring` runs at a fixed rate, advancing from each scheduled time regardless of how
+    /// long the handler takes. A fixed-delay job instead waits a full `interval` after each
+    /// run completes, so slow runs push later ones back.
+    pub fn fixed_delay(mut self, interval: RecurringInterval, start_time: Option<ScheduleTime>) -> Self {
+        self = self.recurring(interval, start_time);
+        if let Some(ScheduleType::Recurring(recurring)) = self.schedules.last_mut().map(|s| &mut s.schedule_type) {
+            recurring.fixed_delay = true;
+        }
+        self
+    }
+
+    /// Push the first run of each recurring schedule back by one interval.
+    ///
+    /// Applied when the job is built, so a job starting now first runs a full period from
+    /// now rather than on the next sweep. Other schedule types are unaffected.
+    pub fn skip_first(mut self) -> Self {
+        self.skip_first = true;
+        self
+    }
+
+    /// Also run the job once on the first sweep after it is added, e.g. to warm a cache.
+    ///
+    /// Applied when the job is built, as an extra one-off run alongside the job's schedules.
+    /// The startup run does not count towards the other schedules' runs or `max_repeat`.
+    pub fn run_on_start(mut self) -> Self {
+        self.run_on_start = true;
         self
     }
 
@@ -125,24 +239,81 @@ impl JobBuilder {
     /// 
     /// This is a convenience method that converts a Duration to an appropriate RecurringInterval.
     pub fn every(self, interval: Duration, start_time: Option<ScheduleTime>) -> Self {
-        let recurring_interval = duration_to_recurring_interval(interval);
+        let recurring_interval = RecurringInterval::from_duration(interval);
         self.recurring(recurring_interval, start_time)
     }
 
+    /// Schedule the job every `interval`, aligned to `anchor` rather than to the start time.
+    ///
+    /// Runs fall on `anchor + k * interval`, starting with the first such time after now, so
+    /// e.g. a 15 minute interval anchored on the hour runs at :00, :15, :30 and :45 however
+    /// late the job is added. Rebuilding the job with the same anchor keeps the same phase.
+    ///
+    /// The interval must be a non-zero whole number of seconds, as recurring intervals are;
+    /// otherwise `try_build` and `Scheduler::add_job` fail with `InvalidSchedule`.
+    pub fn every_aligned(mut self, interval: Duration, anchor: SystemTime) -> Self {
+        if interval.is_zero() || interval.subsec_nanos() != 0 {
+            self.build_error.get_or_insert(JobSchedulerError::InvalidSchedule(format!(
+                "aligned interval must be a non-zero whole number of seconds, got {:?}",
+                interval
+            )));
+            return self;
+        }
+        let first_run = aligned_after(anchor, interval, SystemTime::now());
+        self.every(interval, Some(ScheduleTime::At(first_run)))
+    }
+
+    /// Schedule the job to run `count` times a minute, e.g. 5 a minute runs every 12 seconds.
+    ///
+    /// Intervals are whole seconds, so a rate that does not divide the minute evenly is
+    /// rounded up to the next achievable rate: 7 a minute runs every 8 seconds. A `count` of
+    /// zero or above 60 cannot be scheduled, and `try_build` and `Scheduler::add_job` then
+    /// fail with `InvalidSchedule`.
+    pub fn rate_per_minute(self, count: u32) -> Self {
+        self.rate(count, 60, "minute")
+    }
+
+    /// Schedule the job to run `count` times an hour, e.g. 4 an hour runs every 15 minutes.
+    ///
+    /// Rounded as for `rate_per_minute`; a `count` of zero or above 3600 cannot be scheduled.
+    pub fn rate_per_hour(self, count: u32) -> Self {
+        self.rate(count, 3600, "hour")
+    }
+
+    // Run `count` times per period of `period_secs`, at whole-second intervals
+    fn rate(mut self, count: u32, period_secs: u64, period: &str) -> Self {
+        if count == 0 || u64::from(count) > period_secs {
+            self.build_error.get_or_insert(JobSchedulerError::InvalidSchedule(format!(
+                "cannot run {} times per {}",
+                count, period
+            )));
+            return self;
+        }
+        self.every(Duration::from_secs(period_secs / u64::from(count)), None)
+    }
+
+    /// Schedule the job to fire `count` times as fast as possible, then stop.
+    ///
+    /// Each `run_pending` sweep fires the burst at most once, so the runs are spread over
+    /// `count` consecutive sweeps.
+    pub fn burst(self, count: u32) -> Self {
+        self.recurring(RecurringInterval::Secondly(0), Some(ScheduleTime::At(SystemTime::now())))
+            .max_repeat(count)
+    }
+
     /// Schedule the job using a cron expression.
     pub fn cron(mut self, cron_schedule: &str) -> Self {
         // Try to parse the cron expression
         match CronSchedule::from_str(cron_schedule) {
             Ok(schedule) => {
-                if let Some(rt) = schedule.upcoming(Utc).next().map(|dt| dt.into()) {
-                    self.next_run = self.next_run.map_or(Some(rt), |nr| Some(nr.min(rt)));
-                }
+                let rt = schedule.upcoming(Utc).next().map(|dt| dt.into());
                 let sched = Schedule { 
-                    schedule_type: ScheduleType::Cron(schedule.clone()), 
+                    schedule_type: ScheduleType::Cron { source: cron_schedule.to_string(), schedule }, 
                     max_runs: None, 
-                    run_count: 0 
+                    run_count: 0,
+                    next_run: rt,
                 };
-                self.schedules.push(sched);
+                self.push_schedule(sched);
             },
             Err(_) => {
                 // In case of an error, don't add this schedule
@@ -152,39 +323,242 @@ impl JobBuilder {
         self
     }
 
+    /// Schedule the job using a cron expression, checking it first.
+    ///
+    /// Unlike `cron`, which ignores expressions it cannot parse, returns a descriptive
+    /// `InvalidSchedule` for malformed fields such as a `*/0` step or a `17-9` range.
+    pub fn try_cron(self, cron_schedule: &str) -> Result<Self, JobSchedulerError> {
+        validate_cron(cron_schedule)?;
+        CronSchedule::from_str(cron_schedule)
+            .map_err(|err| JobSchedulerError::InvalidSchedule(err.to_string()))?;
+        Ok(self.cron(cron_schedule))
+    }
+
+    /// Schedule the job to run once, at the next time matching a cron expression.
+    pub fn cron_once(self, cron_schedule: &str) -> Self {
+        let count = self.schedules.len();
+        let mut job = self.cron(cron_schedule);
+        // An invalid expression adds no schedule to limit
+        if job.schedules.len() > count
+            && let Some(sched) = job.schedules.last_mut()
+        {
+            sched.max_runs = Some(1);
+        }
+        job
+    }
+
+    /// Schedule the job on every `n`th match of a cron expression.
+    ///
+    /// The first run is at the next match, after which `n - 1` matches are skipped between
+    /// runs, so `cron_every_nth("0 0 9 * * Mon *", 2)` runs every other Monday.
+    pub fn cron_every_nth(mut self, cron_schedule: &str, n: u32) -> Self {
+        if let Ok(schedule) = CronSchedule::from_str(cron_schedule) {
+            let rt = schedule.upcoming(Utc).next().map(SystemTime::from);
+            let sched = Schedule {
+                schedule_type: ScheduleType::CronEveryNth(NthCronSchedule { schedule, n: n.max(1) }),
+                max_runs: None,
+                run_count: 0,
+                next_run: rt,
+            };
+            self.push_schedule(sched);
+        }
+        self
+    }
+
+    /// Schedule the job using a cron expression, skipping matches on weekends or `holidays`.
+    ///
+    /// A match on a Saturday, Sunday or listed holiday (all UTC) moves to the next match on
+    /// a business day, so a daily expression runs Monday to Friday outside holidays.
+    pub fn cron_business_days(mut self, cron_schedule: &str, holidays: Vec<NaiveDate>) -> Self {
+        if let Ok(schedule) = CronSchedule::from_str(cron_schedule) {
+            let business = BusinessDayCronSchedule { schedule, holidays };
+            let rt = business.next_after(SystemTime::now());
+            let sched = Schedule {
+                schedule_type: ScheduleType::BusinessDayCron(business),
+                max_runs: None,
+                run_count: 0,
+                next_run: rt,
+            };
+            self.push_schedule(sched);
+        }
+        self
+    }
+
+    /// Schedule the job using a cron expression matched against local time in `timezone`.
+    ///
+    /// Local times skipped or repeated by daylight saving changes are handled by the
+    /// schedule's `DstPolicy`, `FireOnce` unless changed with `dst_policy`.
+    pub fn cron_tz(mut self, cron_schedule: &str, timezone: Tz) -> Self {
+        if let Ok(schedule) = CronSchedule::from_str(cron_schedule) {
+            let zoned = ZonedCronSchedule { source: cron_schedule.to_string(), schedule, timezone, dst_policy: DstPolicy::default() };
+            let rt = zoned.next_after(SystemTime::now());
+            let sched = Schedule {
+                schedule_type: ScheduleType::ZonedCron(zoned),
+                max_runs: None,
+                run_count: 0,
+                next_run: rt,
+            };
+            self.push_schedule(sched);
+        }
+        self
+    }
+
+    /// Schedule the job to run every day at each of `times`, as local times in `timezone`.
+    ///
+    /// A more readable alternative to cron for a few fixed times a day, e.g. 08:00, 12:00
+    /// and 17:00. After the day's last time the job next runs at the first time tomorrow.
+    /// An empty list of times cannot be scheduled.
+    pub fn at_times(mut self, mut times: Vec<NaiveTime>, timezone: Tz) -> Self {
+        if times.is_empty() {
+            self.build_error.get_or_insert(JobSchedulerError::InvalidSchedule(
+                "at_times needs at least one time".to_string(),
+            ));
+            return self;
+        }
+        times.sort();
+        times.dedup();
+        let daily = DailyTimesSchedule { times, timezone };
+        let rt = daily.next_after(SystemTime::now());
+        let sched = Schedule {
+            schedule_type: ScheduleType::AtTimes(daily),
+            max_runs: None,
+            run_count: 0,
+            next_run: rt,
+        };
+        self.push_schedule(sched);
+        self
+    }
+
+    /// Set how the most recently added `cron_tz` schedule handles daylight saving changes.
+    pub fn dst_policy(mut self, policy: DstPolicy) -> Self {
+        if let Some(sched) = self.schedules.last_mut()
+            && let ScheduleType::ZonedCron(zoned) = &mut sched.schedule_type
+        {
+            zoned.dst_policy = policy;
+            sched.next_run = zoned.next_after(SystemTime::now());
+        }
+        self
+    }
+
     /// Schedule the job at a random time between start_time and end_time.
-    pub fn random(mut self, start: ScheduleTime, end: ScheduleTime) -> Self {
+    pub fn random(self, start: ScheduleTime, end: ScheduleTime) -> Self {
+        self.random_weighted(start, end, Distribution::Uniform)
+    }
+
+    /// Schedule the job to run once at a random time between start and end, biased as
+    /// `distribution` dictates.
+    ///
+    /// `FrontLoaded` favours times near `start` and `BackLoaded` times near `end`; `random`
+    /// is the same as passing `Uniform`.
+    pub fn random_weighted(self, start: ScheduleTime, end: ScheduleTime, distribution: Distribution) -> Self {
+        self.push_random(start, end, distribution, &mut rng())
+    }
+
+    /// Schedule the job at a random time between start_time and end_time, picked with an RNG
+    /// seeded from `seed`.
+    ///
+    /// The same seed and window always pick the same time, which keeps tests of random
+    /// scheduling reproducible. Delays are measured from when the method is called, so use
+    /// absolute times for a fully reproducible run time.
+    pub fn random_seeded(self, start: ScheduleTime, end: ScheduleTime, seed: u64) -> Self {
+        self.push_random(start, end, Distribution::Uniform, &mut StdRng::seed_from_u64(seed))
+    }
+
+    // Add a one-off random schedule, picking its time with `rng`
+    fn push_random<R: Rng + ?Sized>(mut self, start: ScheduleTime, end: ScheduleTime, distribution: Distribution, rng: &mut R) -> Self {
         // Convert both times to SystemTime
-        let start_time = match start {
-            ScheduleTime::At(time) => time,
-            ScheduleTime::Delay(delay) => SystemTime::now() + delay,
-        };
-        
-        let end_time = match end {
-            ScheduleTime::At(time) => time,
-            ScheduleTime::Delay(delay) => SystemTime::now() + delay,
+        let (Some(start_time), Some(end_time)) = (self.resolve_time(&start), self.resolve_time(&end)) else {
+            return self;
         };
         
-        let rand_sched = RandomSchedule { start_time, end_time };
-        let rt = if end_time > start_time {
-            let range = end_time.duration_since(start_time).unwrap();
-            let mut rng = rng();
-            let nanos = range.as_nanos() as u64;
-            let offset = rng.random_range(0..nanos);
-            Some(start_time + Duration::from_nanos(offset))
-        } else { None };
-        
-        if let Some(rn) = rt {
-            self.next_run = self.next_run.map_or(Some(rn), |nr| Some(nr.min(rn)));
-        }
+        let rand_sched = RandomSchedule { start_time, end_time, distribution };
+        let rt = distribution.sample_between(rng, start_time, end_time);
         
         let sched = Schedule { 
             schedule_type: ScheduleType::Random(rand_sched), 
-            max_runs: None, 
-            run_count: 0 
+            max_runs: Some(1), 
+            run_count: 0,
+            next_run: rt,
         };
         
-        self.schedules.push(sched);
+        self.push_schedule(sched);
+        self
+    }
+
+    /// Schedule the job to recur, running at a random time within a daily window each cycle.
+    ///
+    /// For example `Daily(1)` with a 01:00-04:00 window runs once a night at an unpredictable
+    /// time. Window times are UTC; a window ending before it starts wraps past midnight.
+    /// There is one window a day, so intervals shorter than a day run daily.
+    pub fn recurring_random_window(mut self, interval: RecurringInterval, window_start: NaiveTime, window_end: NaiveTime) -> Self {
+        let now = SystemTime::now();
+        let mut window = RandomWindowSchedule {
+            interval: interval.normalize(),
+            window_start,
+            window_end,
+            cycle_day: midnight_utc(now),
+        };
+        // Today's window may already have closed
+        if window.window().1 <= now {
+            window.advance_cycle();
+        }
+        let (start, end) = window.window();
+        let rt = random_time_between(&mut rng(), start.max(now), end);
+        let sched = Schedule {
+            schedule_type: ScheduleType::RandomWindow(window),
+            max_runs: None,
+            run_count: 0,
+            next_run: rt,
+        };
+        self.push_schedule(sched);
+        self
+    }
+
+    /// Make the job depend on another job.
+    ///
+    /// When due, the job only runs once the dependency has completed successfully since this
+    /// job last ran; until then it stays due and is re-checked on each sweep. A dependency
+    /// that is never added holds the job back for good; `Scheduler::validate_all` reports it.
+    pub fn after(mut self, dependency_id: Uuid) -> Self {
+        self.dependencies.push(dependency_id);
+        self
+    }
+
+    /// Run the job `delay` after each successful run of the job with id `other_id`.
+    ///
+    /// Unlike `after`, which only holds the job back until its dependencies have run, this
+    /// schedules the job itself: whenever the other job runs, this job's next run becomes
+    /// the other job's last run plus `delay`.
+    pub fn after_job(mut self, other_id: Uuid, delay: Duration) -> Self {
+        let sched = Schedule {
+            schedule_type: ScheduleType::AfterJob(AfterJobSchedule { job_id: other_id, delay }),
+            max_runs: None,
+            run_count: 0,
+            next_run: None,
+        };
+        self.push_schedule(sched);
+        self
+    }
+
+    /// Schedule the job from a textual schedule specification.
+    ///
+    /// Accepts any form understood by `ScheduleSpec`, e.g. `every:5m` or `cron:0 0 * * * * *`,
+    /// and returns `InvalidSchedule` if it cannot be parsed.
+    pub fn schedule_str(self, spec: &str) -> Result<Self, JobSchedulerError> {
+        Ok(match spec.parse::<ScheduleSpec>()? {
+            ScheduleSpec::Once(time) => self.once(time),
+            ScheduleSpec::Every(interval) => self.every(interval, None),
+            ScheduleSpec::Cron(schedule) => self.cron(&schedule.to_string()),
+            ScheduleSpec::Random(start, end) => self.random(start, end),
+        })
+    }
+
+    /// Shift every computed run of the job by `delta`, earlier if `before` is set.
+    ///
+    /// Composes with all schedule types, e.g. a midnight cron with a 10 minute `before`
+    /// offset runs at 23:50. A shifted time that is already in the past is due immediately.
+    pub fn offset(mut self, delta: Duration, before: bool) -> Self {
+        self.offset = Some(RunOffset { delta, before });
         self
     }
 
@@ -196,52 +570,311 @@ impl JobBuilder {
         self
     }
 
+    /// Only run the job between `start` and `end` (UTC) each day.
+    ///
+    /// Runs that would fall outside the window are postponed to the window's next opening;
+    /// recurring schedules then continue their cadence from there, and cron schedules move to
+    /// their first match inside the window. Random windows pick again from later days, while
+    /// `now_once` and `run_on_start` runs and opportunistic deadlines are not held back. A
+    /// window ending at or before its start wraps past midnight.
+    pub fn only_between(mut self, start: NaiveTime, end: NaiveTime) -> Self {
+        self.active_window = Some(TimeWindow { start, end });
+        self
+    }
+
+    /// Run the job's handler on a new thread named after the job rather than inline.
+    ///
+    /// The thread name shows up in profilers, stack traces and panic messages. The job does
+    /// not start another run until the previous one has finished.
+    pub fn dedicated_thread(mut self) -> Self {
+        self.dedicated_thread = true;
+        self
+    }
+
+    /// Fire at most once per sweep for any number of missed runs, for idempotent jobs.
+    ///
+    /// Without this a job that fell several intervals behind catches up one run per sweep,
+    /// up to the scheduler's `max_catchup`. A coalescing job instead runs once and moves its
+    /// recurring schedules past every occurrence missed up to the sweep's time.
+    pub fn coalesce(mut self) -> Self {
+        self.coalesce = true;
+        self
+    }
+
+    /// Rank the job against others, for `SortKey::Priority`. Higher values come first;
+    /// jobs default to 0.
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
     /// Assign a handler to the job. Accepts a closure that takes no arguments and returns nothing.
     pub fn add_handler<F>(mut self, handler: F) -> Self 
     where F: Fn() + Send + 'static {
-        self.handler = Some(Box::new(handler));
+        self.handler = Some(Box::new(move |_, _| {
+            handler();
+            Ok(())
+        }));
         self
     }
 
-    /// Finalize the builder.
-    pub fn build(self) -> JobBuilder {
-        JobBuilder { ..self }
+    /// Assign a handler that receives metadata about the job it is running for.
+    ///
+    /// Lets one handler serve many jobs and still tell them apart, e.g. to log the job's name.
+    pub fn add_handler_with_meta<F>(mut self, handler: F) -> Self
+    where F: Fn(&JobMeta) + Send + 'static {
+        self.handler = Some(Box::new(move |meta, _| {
+            handler(meta);
+            Ok(())
+        }));
+        self
     }
-}
 
-impl JobExecutor for JobBuilder {
-    fn run(&mut self) -> Result<(), JobSchedulerError> {
-        if let Some(handler) = &self.handler {
-            handler();
+    /// Assign a handler whose return value is kept for the driver to collect.
+    ///
+    /// Each run's value, e.g. the number of rows processed, is queued on the job until taken
+    /// with `Scheduler::take_results`. Values are kept until taken, so drain them regularly.
+    pub fn add_handler_returning<T, F>(mut self, handler: F) -> Self
+    where
+        T: Send + 'static,
+        F: Fn() -> T + Send + 'static,
+    {
+        let results = Arc::clone(&self.results);
+        self.handler = Some(Box::new(move |_, _| {
+            let value = handler();
+            results.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push_back(Box::new(value));
             Ok(())
-        } else {
-            Err(JobSchedulerError::HandlerNotBuilt)
+        }));
+        self
+    }
+
+    /// Only run the job when `guard` returns true.
+    ///
+    /// The guard is checked each time the job is due. When it returns false the run is
+    /// skipped and recorded as `RunOutcome::Skipped`, and the job moves on to its next
+    /// occurrence, which checks the guard again. Skipped runs do not count towards `max_repeat`.
+    pub fn run_if<F>(mut self, guard: F) -> Self
+    where F: Fn() -> bool + Send + 'static {
+        self.guard = Some(Box::new(guard));
+        self
+    }
+
+    /// Fire each run at most once per key, for schedulers sharing state across processes.
+    ///
+    /// `key` maps the time a run is scheduled for to a key, e.g. the hour it falls in. Before
+    /// the run fires the scheduler claims the key from its `DedupStore`; if the key was
+    /// already claimed the run is skipped as with `run_if`. Retries under a `retry` policy,
+    /// and runs that failed and stayed due, reuse the job's own claim. Has no effect unless the scheduler has a store
+    /// set with `Scheduler::dedup_store`.
+    pub fn idempotency_key<F>(mut self, key: F) -> Self
+    where F: Fn(SystemTime) -> String + Send + 'static {
+        self.idempotency_key = Some(Box::new(key));
+        self
+    }
+
+    /// Retry a failed run, up to `max_attempts` runs in total, waiting between attempts as
+    /// `strategy` dictates.
+    ///
+    /// The job's schedule only advances, and its error handlers are only called, once the
+    /// last attempt has failed.
+    pub fn retry_with(mut self, max_attempts: u32, strategy: BackoffStrategy) -> Self {
+        self.retry = Some(RetryPolicy { max_attempts, strategy });
+        self
+    }
+
+    /// Assign a handler called when the job fails while run by a scheduler.
+    ///
+    /// Takes precedence over the scheduler's `on_any_error` handler.
+    pub fn on_error<F>(mut self, handler: F) -> Self
+    where F: Fn(&JobSchedulerError) + Send + 'static {
+        self.error_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Assign a handler called once the job has made its last run while run by a scheduler.
+    ///
+    /// Fires when every schedule is used up, e.g. after the final run of a `max_repeat` job
+    /// or a one-off job, or a cron expression's last match. Jobs that recur indefinitely
+    /// never complete.
+    pub fn on_complete<F>(mut self, handler: F) -> Self
+    where F: Fn() + Send + 'static {
+        self.complete_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Attach arbitrary context data to the job, made available to `add_handler_with_ctx` handlers.
+    pub fn with_context<T: Send + 'static>(mut self, ctx: T) -> Self {
+        self.context = Some(Box::new(ctx));
+        self
+    }
+
+    /// Assign a handler that receives a reference to the job's context.
+    ///
+    /// Running the job fails with `ExecutionFailed` if no context of type `T` was attached with `with_context`.
+    pub fn add_handler_with_ctx<T, F>(mut self, handler: F) -> Self
+    where
+        T: Send + 'static,
+        F: Fn(&T) + Send + 'static,
+    {
+        self.handler = Some(Box::new(move |_, ctx| {
+            match ctx.and_then(|ctx| ctx.downcast_ref::<T>()) {
+                Some(ctx) => {
+                    handler(ctx);
+                    Ok(())
+                }
+                None => Err(JobSchedulerError::ExecutionFailed(
+                    "job context is missing or not of the expected type".to_string(),
+                )),
+            }
+        }));
+        self
+    }
+
+    /// Remove schedules that duplicate an earlier one.
+    ///
+    /// Two `once` calls with the same time, two `cron` calls with the same expression, or two
+    /// identical recurring schedules collapse into the first. Random schedules are kept since
+    /// each picks its own time, as are schedules limited to a different number of runs.
+    /// `build` calls this automatically.
+    pub fn dedup_schedules(mut self) -> Self {
+        let mut unique: Vec<Schedule> = Vec::with_capacity(self.schedules.len());
+        for sched in self.schedules.drain(..) {
+            if !unique.iter().any(|kept| {
+                kept.max_runs == sched.max_runs && kept.schedule_type.duplicates(&sched.schedule_type)
+            }) {
+                unique.push(sched);
+            }
+        }
+        self.schedules = unique;
+        self
+    }
+
+    /// Finalize the builder into a `Job`, collapsing duplicate schedules.
+    pub fn build(self) -> Job {
+        let mut builder = self.dedup_schedules();
+        if builder.skip_first {
+            builder.skip_first_runs();
+        }
+        // Added after deduplication so it is never merged into a `now_once` schedule
+        if builder.run_on_start {
+            builder.schedules.push(immediate_schedule());
+        }
+        let mut job = Job {
+            id: builder.id,
+            name: builder.name,
+            schedules: builder.schedules,
+            last_run: None,
+            next_run: None,
+            handler: builder.handler,
+            context: builder.context,
+            dependencies: builder.dependencies,
+            error_handler: builder.error_handler,
+            complete_handler: builder.complete_handler,
+            guard: builder.guard,
+            offset: builder.offset,
+            dedicated_thread: builder.dedicated_thread,
+            coalesce: builder.coalesce,
+            priority: builder.priority,
+            active_window: builder.active_window,
+            retry: builder.retry,
+            failed_attempts: 0,
+            paused: false,
+            idempotency_key: builder.idempotency_key,
+            claimed_key: None,
+            unbound: false,
+            consecutive_panics: 0,
+            build_error: builder.build_error,
+            history: Vec::new(),
+            failure_count: 0,
+            drifts: Vec::new(),
+            results: builder.results,
+        };
+        if let Some(window) = job.active_window {
+            job.schedules.iter_mut().for_each(|sched| window.constrain(sched));
         }
+        job.refresh_next_run();
+        job
     }
+
+    /// Finalize the builder, checking that the job has a handler and at least one schedule.
+    ///
+    /// Returns `MissingSchedule` or `HandlerNotBuilt` so misconfiguration is caught at build
+    /// time rather than when the job is added to a scheduler.
+    pub fn try_build(self) -> Result<Job, JobSchedulerError> {
+        let job = self.build();
+        job.ensure_runnable()?;
+        Ok(job)
+    }
+
+    /// Check that every schedule can fire.
+    ///
+    /// Returns `InvalidSchedule` for a cron expression with no upcoming occurrence, such as
+    /// `0 0 30 2 * * *` (30 February), which would otherwise leave the job silently idle.
+    /// `Scheduler::add_job` performs the same check.
+    pub fn validate(&self) -> Result<(), JobSchedulerError> {
+        for sched in &self.schedules {
+            sched.validate()?;
+        }
+        Ok(())
+    }
+
+    // Resolve a schedule time, recording an error instead of panicking if it overflows
+    fn resolve_time(&mut self, time: &ScheduleTime) -> Option<SystemTime> {
+        let resolved = time.resolve(SystemTime::now());
+        if resolved.is_none() && self.build_error.is_none() {
+            self.build_error = Some(JobSchedulerError::TimeCalculationError);
+        }
+        resolved
+    }
+
+    // Move each recurring schedule's first run one interval later
+    fn skip_first_runs(&mut self) {
+        for sched in self.schedules.iter_mut() {
+            if let ScheduleType::Recurring(recurring) = &mut sched.schedule_type {
+                match recurring.following(recurring.next_run) {
+                    Some(next) => {
+                        recurring.next_run = next;
+                        sched.next_run = Some(next);
+                    }
+                    None => {
+                        self.build_error.get_or_insert(JobSchedulerError::TimeCalculationError);
+                    }
+                }
+            }
+        }
+    }
+
+    // Add a schedule to the job
+    fn push_schedule(&mut self, sched: Schedule) {
+        self.schedules.push(sched);
+    }
+
 }
 
-// Helper function for backward compatibility with the every method
-fn duration_to_recurring_interval(duration: Duration) -> RecurringInterval {
-    let secs = duration.as_secs();
-    
-    if secs % 86400 == 0 && secs > 0 {
-        // Daily (86400 seconds in a day)
-        RecurringInterval::Daily((secs / 86400) as u32)
-    } else if secs % 3600 == 0 && secs > 0 {
-        // Hourly (3600 seconds in an hour)
-        RecurringInterval::Hourly((secs / 3600) as u32)
-    } else if secs % 60 == 0 && secs > 0 {
-        // Minutely (60 seconds in a minute)
-        RecurringInterval::Minutely((secs / 60) as u32)
-    } else {
-        // Secondly
-        RecurringInterval::Secondly(secs as u32)
+// A single run on the next sweep, reporting the time it was created as its next run
+fn immediate_schedule() -> Schedule {
+    let created = SystemTime::now();
+    Schedule { schedule_type: ScheduleType::Immediate, max_runs: Some(1), run_count: 0, next_run: Some(created) }
+}
+
+// Smallest `anchor + k * interval` strictly after `now`, or the anchor itself if it is later
+fn aligned_after(anchor: SystemTime, interval: Duration, now: SystemTime) -> SystemTime {
+    let Ok(elapsed) = now.duration_since(anchor) else {
+        return anchor;
+    };
+    if interval.is_zero() {
+        return now;
     }
+    let periods = elapsed.as_nanos() / interval.as_nanos() + 1;
+    let offset = interval.as_nanos() * periods;
+    anchor + Duration::new((offset / 1_000_000_000) as u64, (offset % 1_000_000_000) as u32)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::job::JobExecutor;
+    use std::thread::sleep;
     use std::time::{SystemTime, Duration};
 
 
@@ -249,7 +882,7 @@ mod tests {
     fn test_schedule_job_once() {
         let job_builder = JobBuilder::new("test_once");
         let target_time = ScheduleTime::At(SystemTime::now() + Duration::from_secs(5));
-        let scheduled_job = job_builder.once(target_time.clone());
+        let scheduled_job = job_builder.once(target_time.clone()).build();
 
         assert!(!scheduled_job.schedules.is_empty());
         let schedule_in_job = &scheduled_job.schedules[0];
@@ -273,11 +906,11 @@ mod tests {
     fn test_schedule_job_cron() {
         let job_builder = JobBuilder::new("test_cron");
         let cron_schedule_expr = "* * * * * * *";
-        let scheduled_job = job_builder.cron(cron_schedule_expr);
+        let scheduled_job = job_builder.cron(cron_schedule_expr).build();
 
         assert!(!scheduled_job.schedules.is_empty());
         let schedule_in_job = &scheduled_job.schedules[0];
-        assert!(matches!(schedule_in_job.schedule_type, ScheduleType::Cron(_)));
+        assert!(matches!(schedule_in_job.schedule_type, ScheduleType::Cron { .. }));
         assert_eq!(schedule_in_job.max_runs, None);
         assert!(scheduled_job.next_run.is_some());
 
@@ -292,7 +925,7 @@ mod tests {
         let job_builder = JobBuilder::new("test_recurring");
         let interval = Duration::from_secs(10);
         let first_run = ScheduleTime::At(SystemTime::now() + interval);
-        let scheduled_job = job_builder.every(interval, Some(first_run.clone()));
+        let scheduled_job = job_builder.every(interval, Some(first_run.clone())).build();
 
         assert!(!scheduled_job.schedules.is_empty());
         let schedule_in_job = &scheduled_job.schedules[0];
@@ -317,24 +950,300 @@ mod tests {
         let job_builder = JobBuilder::new("test_random");
         let start_time = ScheduleTime::At(SystemTime::now() + Duration::from_secs(1));
         let end_time = ScheduleTime::At(SystemTime::now() + Duration::from_secs(10));
-        let scheduled_job = job_builder.random(start_time.clone(), end_time.clone());
+        let scheduled_job = job_builder.random(start_time.clone(), end_time.clone()).build();
         let next_run = scheduled_job.next_run.unwrap();
-        if let ScheduleTime::At(start_time) = start_time {
-            if let ScheduleTime::At(end_time) = end_time {
-                assert!(next_run >= start_time && next_run < end_time);
-            }
+        if let (ScheduleTime::At(start_time), ScheduleTime::At(end_time)) = (start_time, end_time) {
+            assert!(next_run >= start_time && next_run < end_time);
         }
     }
 
+    #[test]
+    fn test_schedule_job_random_zero_width() {
+        let at = SystemTime::now() + Duration::from_secs(5);
+        let scheduled_job = JobBuilder::new("test_random_zero_width")
+            .random(ScheduleTime::At(at), ScheduleTime::At(at)).build();
+        assert_eq!(scheduled_job.next_run, Some(at));
+    }
+
+    #[test]
+    fn test_schedule_job_random_very_large_window() {
+        // ~10,000 years, well beyond the u64 nanosecond range
+        let start = SystemTime::UNIX_EPOCH;
+        let end = start + Duration::from_secs(10_000 * 365 * 86400);
+        let scheduled_job = JobBuilder::new("test_random_large")
+            .random(ScheduleTime::At(start), ScheduleTime::At(end)).build();
+        let next_run = scheduled_job.next_run.unwrap();
+        assert!(next_run >= start && next_run < end);
+    }
+
     #[test]
     fn test_schedule_job_random_invalid_range() {
         let job_builder = JobBuilder::new("test_random_invalid");
         let start_time = ScheduleTime::At(SystemTime::now() + Duration::from_secs(10));
         let end_time = ScheduleTime::At(SystemTime::now() - Duration::from_secs(1));
-        let scheduled_job = job_builder.random(start_time, end_time);
+        let scheduled_job = job_builder.random(start_time, end_time).build();
         assert!(scheduled_job.next_run.is_none());
     }
 
+    #[test]
+    fn test_handler_with_context() {
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_clone = Arc::clone(&seen);
+        let mut job = JobBuilder::new("test_context")
+            .with_context(String::from("tenant-a"))
+            .add_handler_with_ctx(move |tenant: &String| {
+                seen_clone.lock().unwrap().push(tenant.clone());
+            })
+            .build();
+
+        job.run().unwrap();
+        assert_eq!(*seen.lock().unwrap(), vec!["tenant-a".to_string()]);
+    }
+
+    #[test]
+    fn test_handler_with_context_wrong_type() {
+        let mut job = JobBuilder::new("test_context_mismatch")
+            .with_context(42u32)
+            .add_handler_with_ctx(|_: &String| {})
+            .build();
+        assert!(matches!(job.run(), Err(JobSchedulerError::ExecutionFailed(_))));
+
+        let mut job = JobBuilder::new("test_context_missing")
+            .add_handler_with_ctx(|_: &String| {})
+            .build();
+        assert!(matches!(job.run(), Err(JobSchedulerError::ExecutionFailed(_))));
+    }
+
+    #[test]
+    fn test_handler_with_meta() {
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_clone = Arc::clone(&seen);
+        let mut job = JobBuilder::new("test_meta")
+            .once(ScheduleTime::Delay(Duration::from_secs(60)))
+            .add_handler_with_meta(move |meta| seen_clone.lock().unwrap().push(meta.clone()))
+            .build();
+
+        job.run().unwrap();
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].id, job.id);
+        assert_eq!(seen[0].name.as_deref(), Some("test_meta"));
+        assert_eq!(seen[0].run_count, 0);
+        assert_eq!(seen[0].scheduled_for, job.next_run);
+    }
+
+    #[test]
+    fn test_random_seeded_is_reproducible() {
+        let start = ScheduleTime::At(SystemTime::now() + Duration::from_secs(60));
+        let end = ScheduleTime::At(SystemTime::now() + Duration::from_secs(3600));
+        let pick = |seed| JobBuilder::new("seeded")
+            .random_seeded(start.clone(), end.clone(), seed)
+            .build()
+            .next_run
+            .unwrap();
+        assert_eq!(pick(42), pick(42));
+        assert_ne!(pick(42), pick(43));
+    }
+
+    #[test]
+    fn test_names_are_kept_verbatim() {
+        assert_eq!(JobBuilder::new("backup").name.as_deref(), Some("backup"));
+        assert_eq!(JobBuilder::new("").name.as_deref(), Some(""));
+        assert_eq!(JobBuilder::new("  ").name.as_deref(), Some("  "));
+        assert_eq!(JobBuilder::new_unnamed().name, None);
+    }
+
+    #[test]
+    fn test_try_build_validates() {
+        let missing_schedule = JobBuilder::new("no-schedule")
+            .add_handler(|| {})
+            .try_build();
+        assert!(matches!(missing_schedule, Err(JobSchedulerError::MissingSchedule)));
+
+        let missing_handler = JobBuilder::new("no-handler")
+            .once(ScheduleTime::Delay(Duration::from_secs(5)))
+            .try_build();
+        assert!(matches!(missing_handler, Err(JobSchedulerError::HandlerNotBuilt)));
+
+        let job = JobBuilder::new("complete")
+            .once(ScheduleTime::Delay(Duration::from_secs(5)))
+            .add_handler(|| {})
+            .try_build();
+        assert!(job.is_ok());
+    }
+
+    #[test]
+    fn test_validate_impossible_cron() {
+        let feb_30 = JobBuilder::new("feb-30")
+            .cron("0 0 0 30 2 * *")
+            .add_handler(|| {});
+        assert!(matches!(feb_30.validate(), Err(JobSchedulerError::InvalidSchedule(_))));
+        assert!(matches!(feb_30.try_build(), Err(JobSchedulerError::InvalidSchedule(_))));
+
+        let hourly = JobBuilder::new("hourly").cron("0 0 * * * * *");
+        assert!(hourly.validate().is_ok());
+    }
+
+    #[test]
+    fn test_delay_overflow_is_reported() {
+        let huge = ScheduleTime::Delay(Duration::from_secs(u64::MAX));
+        let job = JobBuilder::new("overflow-once")
+            .once(huge.clone())
+            .add_handler(|| {});
+        assert!(job.schedules.is_empty());
+        assert!(matches!(job.try_build(), Err(JobSchedulerError::TimeCalculationError)));
+
+        let job = JobBuilder::new("overflow-recurring")
+            .recurring(RecurringInterval::Secondly(1), Some(huge.clone()))
+            .once(ScheduleTime::Delay(Duration::from_secs(5)))
+            .add_handler(|| {});
+        assert_eq!(job.schedules.len(), 1);
+        assert!(matches!(job.try_build(), Err(JobSchedulerError::TimeCalculationError)));
+
+        let job = JobBuilder::new("overflow-random")
+            .random(ScheduleTime::Delay(Duration::from_secs(1)), huge)
+            .add_handler(|| {})
+            .build();
+        assert!(job.schedules.is_empty());
+    }
+
+    #[test]
+    fn test_schedule_str() {
+        let job = JobBuilder::new("test_schedule_str")
+            .schedule_str("every:5m").unwrap()
+            .schedule_str("cron:0 0 * * * * *").unwrap();
+        assert_eq!(job.schedules.len(), 2);
+        if let ScheduleType::Recurring(rec) = &job.schedules[0].schedule_type {
+            assert_eq!(rec.interval, RecurringInterval::Minutely(5));
+        } else {
+            panic!("Expected Recurring schedule type");
+        }
+        assert!(matches!(job.schedules[1].schedule_type, ScheduleType::Cron { .. }));
+
+        let err = JobBuilder::new("test_schedule_str_invalid").schedule_str("every:never");
+        assert!(matches!(err, Err(JobSchedulerError::InvalidSchedule(_))));
+    }
+
+    #[test]
+    fn test_try_cron() {
+        let job = JobBuilder::new("test_try_cron")
+            .try_cron("*/5 * * * * * *").unwrap()
+            .try_cron("0 0 9-17 * * * *").unwrap();
+        assert_eq!(job.schedules.len(), 2);
+
+        let err = JobBuilder::new("test_try_cron").try_cron("*/0 * * * * * *").err().unwrap();
+        match err {
+            JobSchedulerError::InvalidSchedule(msg) => assert!(msg.contains("step")),
+            other => panic!("Expected InvalidSchedule, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cron_tz() {
+        let job = JobBuilder::new("test_cron_tz")
+            .cron_tz("0 30 1 * * * *", chrono_tz::Europe::London)
+            .dst_policy(DstPolicy::FireBoth)
+            .build();
+        match &job.schedules[0].schedule_type {
+            ScheduleType::ZonedCron(zoned) => {
+                assert_eq!(zoned.timezone, chrono_tz::Europe::London);
+                assert_eq!(zoned.dst_policy, DstPolicy::FireBoth);
+            }
+            _ => panic!("Expected ZonedCron schedule type"),
+        }
+        assert!(job.next_run.unwrap() > SystemTime::now());
+    }
+
+    #[test]
+    fn test_aligned_after() {
+        let anchor = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let quarter = Duration::from_secs(15 * 60);
+        let at = |mins: u64| anchor + Duration::from_secs(mins * 60);
+        assert_eq!(aligned_after(anchor, quarter, at(20)), at(30));
+        // A run exactly at `now` is not in the future, so the next slot is used
+        assert_eq!(aligned_after(anchor, quarter, at(30)), at(45));
+        assert_eq!(aligned_after(at(60), quarter, anchor), at(60));
+    }
+
+    #[test]
+    fn test_every_aligned_across_restart() {
+        let interval = Duration::from_secs(5);
+        let anchor = SystemTime::now() - Duration::from_millis(12_345);
+        // Build the same job twice, as a restarted process would
+        for _ in 0..2 {
+            let now = SystemTime::now();
+            let job = JobBuilder::new("test_every_aligned")
+                .every_aligned(interval, anchor)
+                .build();
+            let next_run = job.next_run.unwrap();
+            assert!(next_run > now && next_run <= now + interval);
+            let since_anchor = next_run.duration_since(anchor).unwrap();
+            assert_eq!(since_anchor.as_nanos() % interval.as_nanos(), 0);
+            sleep(Duration::from_millis(50));
+        }
+
+        // Later runs stay on the anchor's grid too
+        let mut job = JobBuilder::new("test_every_aligned").every_aligned(interval, anchor).build();
+        let mut run = job.next_run.unwrap();
+        for _ in 0..3 {
+            let next = job.schedules[0].advance_at(run).unwrap();
+            assert_eq!(next.duration_since(run).unwrap(), interval);
+            assert_eq!(next.duration_since(anchor).unwrap().as_nanos() % interval.as_nanos(), 0);
+            run = next;
+        }
+
+        // Intervals that recurring schedules cannot keep exactly are rejected
+        for interval in [Duration::ZERO, Duration::from_millis(500), Duration::from_millis(1500)] {
+            let built = JobBuilder::new("test_every_aligned")
+                .every_aligned(interval, anchor)
+                .add_handler(|| {})
+                .try_build();
+            assert!(matches!(built, Err(JobSchedulerError::InvalidSchedule(_))));
+        }
+    }
+
+    #[test]
+    fn test_dedup_schedules() {
+        let at = ScheduleTime::At(SystemTime::now() + Duration::from_secs(30));
+        let job = JobBuilder::new("test_dedup")
+            .cron("0 0 * * * * *")
+            .cron("0 0 * * * * *")
+            .once(at.clone())
+            .once(at)
+            .cron("0 30 * * * * *")
+            .add_handler(|| {})
+            .build();
+        assert_eq!(job.schedules.len(), 3);
+        assert!(matches!(job.schedules[0].schedule_type, ScheduleType::Cron { .. }));
+        assert!(matches!(job.schedules[1].schedule_type, ScheduleType::Once(_)));
+        assert!(matches!(job.schedules[2].schedule_type, ScheduleType::Cron { .. }));
+
+        // Random schedules are never treated as duplicates
+        let start = ScheduleTime::Delay(Duration::from_secs(1));
+        let end = ScheduleTime::Delay(Duration::from_secs(10));
+        let job = JobBuilder::new("test_dedup_random")
+            .random(start.clone(), end.clone())
+            .random(start, end)
+            .dedup_schedules();
+        assert_eq!(job.schedules.len(), 2);
+
+        // A capped schedule does not swallow an unlimited one
+        let job = JobBuilder::new("test_dedup_max_runs")
+            .cron("0 0 * * * * *")
+            .max_repeat(3)
+            .cron("0 0 * * * * *")
+            .cron("0 0 * * * * *")
+            .max_repeat(3)
+            .dedup_schedules();
+        assert_eq!(job.schedules.len(), 2);
+        assert_eq!(job.schedules[0].max_runs, Some(3));
+        assert_eq!(job.schedules[1].max_runs, None);
+    }
+
     #[test]
     fn test_schedule_job_recurring_direct() {
         let start_time = Some(ScheduleTime::At(SystemTime::now() + Duration::from_secs(5)));
@@ -368,12 +1277,103 @@ mod tests {
             panic!("Expected Recurring schedule type");
         }
 
+        // Recognized custom expressions are normalized to their preset variant
+        let custom_job = JobBuilder::new("test_direct_recurring")
+            .recurring(RecurringInterval::Custom { expression: "weekly".to_string(), frequency: 1 }, start_time.clone());
+        if let ScheduleType::Recurring(rec) = &custom_job.schedules[0].schedule_type {
+            assert_eq!(rec.interval, RecurringInterval::Weekly(1));
+        } else {
+            panic!("Expected Recurring schedule type");
+        }
+
         // Test without explicit start time (should use default)
-        let minutely_job = JobBuilder::new("test_direct_recurring").recurring(RecurringInterval::Minutely(5), None);
+        let minutely_job = JobBuilder::new("test_direct_recurring").recurring(RecurringInterval::Minutely(5), None).build();
         assert!(!minutely_job.schedules.is_empty());
         assert!(minutely_job.next_run.is_some());
         let now = SystemTime::now();
         assert!(minutely_job.next_run.unwrap() > now);
     }
-}
 
+    #[test]
+    fn test_skip_first() {
+        let start = SystemTime::now();
+        let job = JobBuilder::new("skip_first")
+            .skip_first()
+            .recurring(RecurringInterval::Hourly(1), Some(ScheduleTime::At(start)))
+            .once(ScheduleTime::At(start))
+            .add_handler(|| {})
+            .build();
+        // The recurring run moves a full period out; the one-off run is untouched
+        assert_eq!(job.schedules[0].next_run, Some(start + Duration::from_secs(3600)));
+        if let ScheduleType::Recurring(rec) = &job.schedules[0].schedule_type {
+            assert_eq!(rec.next_run, start + Duration::from_secs(3600));
+        } else {
+            panic!("Expected Recurring schedule type");
+        }
+        assert_eq!(job.schedules[1].next_run, Some(start));
+        assert_eq!(job.next_run, Some(start));
+    }
+
+    #[test]
+    fn test_recurring_extreme_intervals() {
+        let intervals = [
+            RecurringInterval::Secondly(u32::MAX),
+            RecurringInterval::Minutely(u32::MAX),
+            RecurringInterval::Hourly(u32::MAX),
+            RecurringInterval::Daily(u32::MAX),
+            RecurringInterval::Weekly(u32::MAX),
+            RecurringInterval::Monthly(u32::MAX),
+            RecurringInterval::Custom { expression: "every".to_string(), frequency: u32::MAX },
+        ];
+        for interval in intervals {
+            let job = JobBuilder::new("extreme")
+                .recurring(interval, None)
+                .add_handler(|| {})
+                .try_build()
+                .unwrap();
+            assert!(job.next_run.unwrap() > SystemTime::now());
+        }
+
+        // Advancing past the representable range ends the schedule instead of panicking
+        let mut job = JobBuilder::new("extreme")
+            .recurring(RecurringInterval::Monthly(u32::MAX), None)
+            .add_handler(|| {})
+            .build();
+        let advances = (0..10_000_000).take_while(|_| job.schedules[0].advance().is_some()).count();
+        assert!(advances < 10_000_000);
+        assert_eq!(job.schedules[0].next_run, None);
+    }
+
+    #[test]
+    fn test_rates() {
+        let interval = |builder: JobBuilder| match &builder.schedules[0].schedule_type {
+            ScheduleType::Recurring(rec) => rec.interval.clone(),
+            _ => panic!("Expected Recurring schedule type"),
+        };
+        // Even rates
+        assert_eq!(interval(JobBuilder::new("rate").rate_per_minute(5)), RecurringInterval::Secondly(12));
+        assert_eq!(interval(JobBuilder::new("rate").rate_per_minute(1)), RecurringInterval::Minutely(1));
+        assert_eq!(interval(JobBuilder::new("rate").rate_per_hour(4)), RecurringInterval::Minutely(15));
+        assert_eq!(interval(JobBuilder::new("rate").rate_per_hour(3600)), RecurringInterval::Secondly(1));
+        // Uneven rates round to the next whole-second interval below
+        assert_eq!(interval(JobBuilder::new("rate").rate_per_minute(7)), RecurringInterval::Secondly(8));
+        assert_eq!(interval(JobBuilder::new("rate").rate_per_hour(7)), RecurringInterval::Secondly(514));
+
+        for builder in [JobBuilder::new("rate").rate_per_minute(0), JobBuilder::new("rate").rate_per_minute(61), JobBuilder::new("rate").rate_per_hour(3601)] {
+            assert!(builder.schedules.is_empty());
+            assert!(matches!(builder.add_handler(|| {}).try_build(), Err(JobSchedulerError::InvalidSchedule(_))));
+        }
+    }
+
+    #[test]
+    fn test_cron_keeps_source() {
+        let expr = "0 30 9 * * Mon-Fri *";
+        let job = JobBuilder::new("standup").cron(expr).add_handler(|| {}).build();
+        let ScheduleType::Cron { source, schedule } = &job.schedules[0].schedule_type else {
+            panic!("expected a cron schedule");
+        };
+        assert_eq!(source, expr);
+        // The source parses back to the same schedule
+        assert!(CronSchedule::from_str(source).unwrap() == *schedule);
+    }
+}
@@ -0,0 +1,88 @@
+//! Retry policies for jobs whose handler fails.
+//!
+//! A `RetryPolicy` re-runs a failed job after a delay chosen by its `BackoffStrategy`, up to a
+//! maximum number of attempts, before the failure is reported to the job's error handlers.
+
+use std::time::Duration;
+use rand::Rng;
+
+/// How long to wait before each retry of a failed run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackoffStrategy {
+    /// Wait the same delay before every retry.
+    Fixed(Duration),
+    /// Double the delay after each retry, starting at `base` and never exceeding `max`.
+    Exponential { base: Duration, max: Duration },
+    /// Wait a uniformly random delay up to the `Exponential` delay, so that many jobs failing
+    /// together do not retry in lockstep.
+    ExponentialJittered { base: Duration, max: Duration },
+}
+
+impl BackoffStrategy {
+    /// Delay before the given retry, counting the first retry as attempt 1.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        match *self {
+            BackoffStrategy::Fixed(delay) => delay,
+            BackoffStrategy::Exponential { base, max } => exponential(base, max, attempt),
+            BackoffStrategy::ExponentialJittered { base, max } => {
+                let ceiling = exponential(base, max, attempt).as_nanos();
+                let nanos = rand::rng().random_range(0..=ceiling);
+                Duration::new((nanos / 1_000_000_000) as u64, (nanos % 1_000_000_000) as u32)
+            }
+        }
+    }
+}
+
+// `base * 2^(attempt - 1)`, capped at `max`
+fn exponential(base: Duration, max: Duration, attempt: u32) -> Duration {
+    let factor = 2u32.checked_pow(attempt.saturating_sub(1)).unwrap_or(u32::MAX);
+    base.checked_mul(factor).map_or(max, |delay| delay.min(max))
+}
+
+/// Retry a failed run up to `max_attempts` runs in total, waiting between them as `strategy`
+/// dictates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub strategy: BackoffStrategy,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_delays() {
+        let strategy = BackoffStrategy::Fixed(Duration::from_secs(5));
+        let delays: Vec<Duration> = (1..=3).map(|attempt| strategy.delay(attempt)).collect();
+        assert_eq!(delays, vec![Duration::from_secs(5); 3]);
+    }
+
+    #[test]
+    fn test_exponential_delays() {
+        let strategy = BackoffStrategy::Exponential {
+            base: Duration::from_secs(1),
+            max: Duration::from_secs(10),
+        };
+        let delays: Vec<u64> = (1..=6).map(|attempt| strategy.delay(attempt).as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 8, 10, 10]);
+        // Growth far beyond the cap does not overflow
+        assert_eq!(strategy.delay(u32::MAX), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_exponential_jittered_delays() {
+        let strategy = BackoffStrategy::ExponentialJittered {
+            base: Duration::from_secs(1),
+            max: Duration::from_secs(10),
+        };
+        for (attempt, ceiling) in [(1, 1), (2, 2), (3, 4), (4, 8), (5, 10), (6, 10)] {
+            for _ in 0..20 {
+                assert!(strategy.delay(attempt) <= Duration::from_secs(ceiling));
+            }
+        }
+        // Draws vary, so retries of many jobs spread out
+        let draws: Vec<Duration> = (0..20).map(|_| strategy.delay(5)).collect();
+        assert!(draws.iter().any(|delay| *delay != draws[0]));
+    }
+}
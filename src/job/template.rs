@@ -0,0 +1,108 @@
+//! JobTemplate captures a job's schedule and configuration so many jobs can be stamped out
+//! from it, each with its own name and handler.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use siafu::{JobBuilder, Scheduler};
+//! use siafu::job::JobTemplate;
+//! use siafu::scheduler::types::RecurringInterval;
+//!
+//! let template = JobTemplate::from(JobBuilder::new_unnamed().recurring(RecurringInterval::Hourly(1), None));
+//! let mut scheduler = Scheduler::new();
+//! let ids = scheduler
+//!     .add_from_template(&template, &["tenant-a", "tenant-b"], |tenant| {
+//!         let tenant = tenant.to_string();
+//!         move || println!("Syncing {}", tenant)
+//!     })
+//!     .unwrap();
+//! assert_eq!(ids.len(), 2);
+//! ```
+
+use uuid::Uuid;
+
+use super::JobBuilder;
+use super::retry::RetryPolicy;
+use crate::error::Error as JobSchedulerError;
+use crate::scheduler::types::{RunOffset, Schedule, TimeWindow};
+
+/// Reusable job configuration without a name, handler or context.
+///
+/// Random schedules keep the time already picked for them, so every job made from the
+/// template shares it.
+#[derive(Clone)]
+pub struct JobTemplate {
+    pub schedules: Vec<Schedule>,
+    pub dependencies: Vec<Uuid>,
+    pub offset: Option<RunOffset>,
+    pub dedicated_thread: bool,
+    pub coalesce: bool,
+    pub priority: i32,
+    pub active_window: Option<TimeWindow>,
+    pub retry: Option<RetryPolicy>,
+    pub skip_first: bool,
+    pub run_on_start: bool,
+    // Error hit while configuring the builder the template came from
+    pub(crate) build_error: Option<JobSchedulerError>,
+}
+
+impl JobTemplate {
+    /// Start a new builder with the template's configuration, a fresh id and the given name.
+    pub fn instantiate(&self, name: &str) -> JobBuilder {
+        let mut builder = JobBuilder::new(name);
+        builder.schedules = self.schedules.clone();
+        builder.dependencies = self.dependencies.clone();
+        builder.offset = self.offset;
+        builder.dedicated_thread = self.dedicated_thread;
+        builder.coalesce = self.coalesce;
+        builder.priority = self.priority;
+        builder.active_window = self.active_window;
+        builder.retry = self.retry;
+        builder.skip_first = self.skip_first;
+        builder.run_on_start = self.run_on_start;
+        builder.build_error = self.build_error.clone();
+        builder
+    }
+}
+
+impl From<JobBuilder> for JobTemplate {
+    /// Keep the builder's configuration, dropping its id, name, handlers and context.
+    fn from(builder: JobBuilder) -> Self {
+        Self {
+            schedules: builder.schedules,
+            dependencies: builder.dependencies,
+            offset: builder.offset,
+            dedicated_thread: builder.dedicated_thread,
+            coalesce: builder.coalesce,
+            priority: builder.priority,
+            active_window: builder.active_window,
+            retry: builder.retry,
+            skip_first: builder.skip_first,
+            run_on_start: builder.run_on_start,
+            build_error: builder.build_error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::job::JobExecutor;
+    use crate::utils::time::ScheduleTime;
+
+    #[test]
+    fn test_instantiate() {
+        let template = JobTemplate::from(JobBuilder::new("ignored")
+            .once(ScheduleTime::Delay(Duration::from_secs(60)))
+            .add_handler(|| {}));
+
+        let first = template.instantiate("first").build();
+        let mut second = template.instantiate("second").build();
+        assert_ne!(first.id, second.id);
+        assert_eq!(first.name.as_deref(), Some("first"));
+        assert_eq!(first.next_run, second.next_run);
+        // Handlers are not carried over
+        assert!(second.run().is_err());
+    }
+}
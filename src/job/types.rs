@@ -0,0 +1,518 @@
+//! Job is the finalized, schedulable form of a job produced by `JobBuilder::build`.
+//!
+//! Holds the job's schedules and handler along with the run state the scheduler updates as
+//! the job fires.
+
+use std::any::Any;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::JobExecutor;
+use super::retry::RetryPolicy;
+use crate::error::Error as JobSchedulerError;
+use crate::scheduler::types::{RunOffset, Schedule, ScheduleType, TimeWindow};
+
+// Define the handler type alias; handlers receive the job's metadata and its context, if any
+pub(crate) type JobHandler = Box<dyn Fn(&JobMeta, Option<&(dyn Any + Send)>) -> Result<(), JobSchedulerError> + Send + 'static>;
+// Called with the error when the job's handler fails
+pub(crate) type JobErrorHandler = Box<dyn Fn(&JobSchedulerError) + Send + 'static>;
+// Called once the job has no runs left
+pub(crate) type JobCompleteHandler = Box<dyn Fn() + Send + 'static>;
+// Checked when the job is due; the run is skipped if it returns false
+pub(crate) type JobGuard = Box<dyn Fn() -> bool + Send + 'static>;
+// Maps a run's scheduled time to the key identifying it, for `JobBuilder::idempotency_key`
+pub(crate) type JobKey = Box<dyn Fn(SystemTime) -> String + Send + 'static>;
+// Values returned by `add_handler_returning` handlers, oldest first, shared with the handler
+pub(crate) type JobResults = Arc<Mutex<VecDeque<Box<dyn Any + Send>>>>;
+
+pub struct Job {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub schedules: Vec<Schedule>,
+    pub last_run: Option<SystemTime>,
+    pub next_run: Option<SystemTime>,
+    pub handler: Option<JobHandler>,
+    pub context: Option<Box<dyn Any + Send>>,
+    pub dependencies: Vec<Uuid>,
+    pub error_handler: Option<JobErrorHandler>,
+    pub complete_handler: Option<JobCompleteHandler>,
+    /// Condition checked each time the job is due; the run is skipped when it is false.
+    pub guard: Option<JobGuard>,
+    pub offset: Option<RunOffset>,
+    /// Run the handler on its own thread, named after the job, instead of inline.
+    pub dedicated_thread: bool,
+    /// Fire once for any number of missed runs, skipping the rest.
+    pub coalesce: bool,
+    /// Rank against other jobs, higher first, as set by `JobBuilder::priority`.
+    pub priority: i32,
+    /// Daily window outside of which the job's runs are postponed.
+    pub active_window: Option<TimeWindow>,
+    /// Retry failed runs before reporting the failure.
+    pub retry: Option<RetryPolicy>,
+    /// Paused jobs are not run until resumed with `Scheduler::resume_job`.
+    pub paused: bool,
+    /// Key of each run, checked against the scheduler's `DedupStore` before it fires.
+    pub idempotency_key: Option<JobKey>,
+    // Idempotency key most recently claimed for a run of this job
+    pub(crate) claimed_key: Option<String>,
+    // Staged by `Scheduler::add_unbound_job` and not yet given a handler
+    pub(crate) unbound: bool,
+    // Consecutive runs that ended in a panic
+    pub(crate) consecutive_panics: u32,
+    // Consecutive failed attempts of the current run
+    pub(crate) failed_attempts: u32,
+    // First error hit while the job was being built, reported by `add_job`
+    pub(crate) build_error: Option<JobSchedulerError>,
+    // Most recent runs, oldest first
+    pub(crate) history: Vec<RunRecord>,
+    // Failed runs over the job's lifetime, unlike `history` which is capped
+    pub(crate) failure_count: u32,
+    // How late the most recent runs started, oldest first and capped like `history`
+    pub(crate) drifts: Vec<Duration>,
+    // Values produced by runs and not yet taken
+    pub(crate) results: JobResults,
+}
+
+/// Metadata about the job being run, passed to `add_handler_with_meta` handlers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobMeta {
+    pub id: Uuid,
+    pub name: Option<String>,
+    /// Number of runs completed before this one.
+    pub run_count: u32,
+    /// Time the run was scheduled for, if the job had an upcoming run.
+    pub scheduled_for: Option<SystemTime>,
+    /// Time the run started, by the scheduler's clock.
+    pub started: SystemTime,
+}
+
+/// Broad lifecycle state of a job, for status summaries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JobState {
+    /// The job has an upcoming run, or is waiting on another job to schedule one.
+    Active,
+    /// The job has been paused and will not run until resumed.
+    Paused,
+    /// Every schedule of the job has used up its runs.
+    Completed,
+}
+
+/// Why a job does or does not have an upcoming run, for diagnosing jobs that never run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleStatus {
+    /// The job will next run at the given time.
+    Active(SystemTime),
+    /// The job has no run of its own until the job with the given id runs.
+    AwaitingJob(Uuid),
+    /// The job has been paused and will not run until resumed.
+    Paused,
+    /// Every schedule of the job has used up its runs.
+    Completed,
+    /// The job has runs left but no schedule has an upcoming occurrence, e.g. a cron
+    /// expression for 30 February.
+    NoUpcomingOccurrence,
+    /// The job was built without a schedule.
+    Unscheduled,
+}
+
+/// Result of a single run of a job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunOutcome {
+    Succeeded,
+    /// The handler failed with the given error message.
+    Failed(String),
+    /// The job's `run_if` guard was false, so the handler was not run.
+    Skipped,
+}
+
+/// A retained record of one run of a job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunRecord {
+    pub started: SystemTime,
+    pub outcome: RunOutcome,
+}
+
+/// Timing statistics for a job's recent runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JobStats {
+    /// How long after its scheduled time the most recent run started.
+    pub last_drift: Option<Duration>,
+    /// Largest drift among the runs kept in the job's history.
+    pub max_drift: Option<Duration>,
+}
+
+/// Owned point-in-time view of a job, suitable for serializing in status reports.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JobSnapshot {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub next_run: Option<SystemTime>,
+    pub last_run: Option<SystemTime>,
+    pub run_count: u32,
+    /// Human-readable description of the job's schedules.
+    pub schedule: String,
+}
+
+impl Job {
+    /// Capture the job's current metadata.
+    pub fn snapshot(&self) -> JobSnapshot {
+        let schedule = self.schedules.iter()
+            .map(|sched| sched.schedule_type.to_string())
+            .collect::<Vec<_>>()
+            .join("; ");
+        JobSnapshot {
+            id: self.id,
+            name: self.name.clone(),
+            next_run: self.next_run,
+            last_run: self.last_run,
+            run_count: self.total_run_count(),
+            schedule,
+        }
+    }
+
+    /// Time remaining until the job's next run.
+    ///
+    /// Returns `None` if the job has no upcoming run, or `Duration::ZERO` if it is already due.
+    pub fn time_until_next(&self) -> Option<Duration> {
+        self.next_run
+            .map(|next| next.duration_since(SystemTime::now()).unwrap_or(Duration::ZERO))
+    }
+
+    /// Time elapsed since the job last ran.
+    ///
+    /// Returns `None` if the job has never run.
+    pub fn since_last_run(&self) -> Option<Duration> {
+        self.last_run
+            .map(|last| SystemTime::now().duration_since(last).unwrap_or(Duration::ZERO))
+    }
+
+    /// Total number of runs across all of the job's schedules.
+    pub fn total_run_count(&self) -> u32 {
+        self.schedules.iter().map(|sched| sched.run_count).sum()
+    }
+
+    /// Number of failed runs since the job was added, counting each failed retry attempt.
+    pub fn total_failures(&self) -> u32 {
+        self.failure_count
+    }
+
+    /// Maximum number of runs across all of the job's schedules.
+    ///
+    /// Returns `None` if any schedule is unbounded, since the job as a whole then is too.
+    pub fn effective_max_runs(&self) -> Option<u32> {
+        self.schedules.iter()
+            .map(|sched| sched.max_runs)
+            .try_fold(0u32, |total, max| max.map(|max| total.saturating_add(max)))
+    }
+
+    /// The job's current lifecycle state.
+    pub fn state(&self) -> JobState {
+        if self.paused {
+            return JobState::Paused;
+        }
+        // `after_job` schedules have no run until the job they follow runs
+        let awaiting_trigger = self.schedules.iter().any(|sched| {
+            matches!(sched.schedule_type, ScheduleType::AfterJob(_))
+                && sched.max_runs.is_none_or(|max| sched.run_count < max)
+        });
+        if self.next_run.is_some() || awaiting_trigger {
+            JobState::Active
+        } else {
+            JobState::Completed
+        }
+    }
+
+    /// Explain whether and when the job will next run.
+    ///
+    /// A finer-grained `state` that tells apart the reasons a job has no upcoming run.
+    pub fn schedule_status(&self) -> ScheduleStatus {
+        if self.schedules.is_empty() {
+            return ScheduleStatus::Unscheduled;
+        }
+        if self.paused {
+            return ScheduleStatus::Paused;
+        }
+        if let Some(next) = self.next_run {
+            return ScheduleStatus::Active(next);
+        }
+        let mut unfinished = self.schedules.iter()
+            .filter(|sched| sched.max_runs.is_none_or(|max| sched.run_count < max))
+            .peekable();
+        if unfinished.peek().is_none() {
+            return ScheduleStatus::Completed;
+        }
+        unfinished
+            .find_map(|sched| match sched.schedule_type {
+                ScheduleType::AfterJob(after) => Some(ScheduleStatus::AwaitingJob(after.job_id)),
+                _ => None,
+            })
+            .unwrap_or(ScheduleStatus::NoUpcomingOccurrence)
+    }
+
+    /// The schedule that produces the job's next run.
+    ///
+    /// Useful for debugging jobs that mix schedule kinds, e.g. cron and recurring. Returns
+    /// `None` if the job has no upcoming run, or if its next run was overridden with a time
+    /// none of its schedules produce, such as by `Scheduler::set_next_run`.
+    pub fn next_schedule(&self) -> Option<&Schedule> {
+        let next = self.next_run?;
+        self.schedules.iter().find(|sched| self.effective_run(sched) == Some(next))
+    }
+
+    /// Timing statistics for the job's recent runs.
+    ///
+    /// Drift is how long after its scheduled time a run actually started. Drift that keeps
+    /// growing means the scheduler cannot keep up, e.g. because earlier jobs in each sweep
+    /// are slow.
+    pub fn stats(&self) -> JobStats {
+        JobStats {
+            last_drift: self.drifts.last().copied(),
+            max_drift: self.drifts.iter().max().copied(),
+        }
+    }
+
+    /// The job's most recent runs, oldest first.
+    ///
+    /// Only the last runs up to the scheduler's history limit are kept.
+    pub fn recent_runs(&self) -> &[RunRecord] {
+        &self.history
+    }
+
+    // Keep a record of a run, evicting the oldest beyond `limit`
+    pub(crate) fn record_run(&mut self, record: RunRecord, limit: usize) {
+        if matches!(record.outcome, RunOutcome::Failed(_)) {
+            self.failure_count = self.failure_count.saturating_add(1);
+        }
+        self.history.push(record);
+        if self.history.len() > limit {
+            let excess = self.history.len() - limit;
+            self.history.drain(..excess);
+        }
+    }
+
+    // Record how late the run starting at `started` is, evicting the oldest beyond `limit`.
+    // Measured from the earliest due schedule; immediate runs have no time to be late for,
+    // and retries are not scheduled runs.
+    pub(crate) fn record_drift(&mut self, started: SystemTime, limit: usize) {
+        if self.failed_attempts > 0 {
+            return;
+        }
+        let scheduled = self.schedules.iter()
+            .filter(|sched| !matches!(sched.schedule_type, ScheduleType::Immediate))
+            .filter_map(|sched| self.effective_run(sched))
+            .filter(|run| *run <= started)
+            .min();
+        let Some(scheduled) = scheduled else {
+            return;
+        };
+        self.drifts.push(started.duration_since(scheduled).unwrap_or(Duration::ZERO));
+        if self.drifts.len() > limit {
+            let excess = self.drifts.len() - limit;
+            self.drifts.drain(..excess);
+        }
+    }
+
+    // Metadata handed to the handler for the run starting at `started`
+    pub(crate) fn meta(&self, started: SystemTime) -> JobMeta {
+        JobMeta {
+            id: self.id,
+            name: self.name.clone(),
+            run_count: self.total_run_count(),
+            scheduled_for: self.next_run,
+            started,
+        }
+    }
+
+    // Run time of a schedule's next occurrence once the job's offset is applied
+    pub(crate) fn effective_run(&self, sched: &Schedule) -> Option<SystemTime> {
+        let run = sched.pending_run()?;
+        Some(self.offset.map_or(run, |offset| offset.apply(run)))
+    }
+
+    // Recompute the job's next run as the earliest next run across its schedules
+    pub(crate) fn refresh_next_run(&mut self) {
+        self.next_run = self.schedules.iter()
+            .filter_map(|sched| self.effective_run(sched))
+            .min();
+    }
+
+    // Check that the job has everything it needs to be scheduled
+    pub(crate) fn ensure_runnable(&self) -> Result<(), JobSchedulerError> {
+        self.ensure_schedulable()?;
+        if self.handler.is_none() {
+            return Err(JobSchedulerError::HandlerNotBuilt);
+        }
+        Ok(())
+    }
+
+    // Check everything `ensure_runnable` does apart from the handler
+    pub(crate) fn ensure_schedulable(&self) -> Result<(), JobSchedulerError> {
+        if let Some(err) = &self.build_error {
+            return Err(err.clone());
+        }
+        if self.schedules.is_empty() {
+            return Err(JobSchedulerError::MissingSchedule);
+        }
+        for sched in &self.schedules {
+            sched.validate()?;
+        }
+        Ok(())
+    }
+}
+
+impl JobExecutor for Job {
+    fn run(&mut self) -> Result<(), JobSchedulerError> {
+        self.run_at(SystemTime::now())
+    }
+
+    fn run_at(&mut self, now: SystemTime) -> Result<(), JobSchedulerError> {
+        if let Some(handler) = &self.handler {
+            handler(&self.meta(now), self.context.as_deref())
+        } else {
+            Err(JobSchedulerError::HandlerNotBuilt)
+        }
+    }
+
+    fn get_next_run(&self) -> Option<SystemTime> {
+        self.next_run
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job::JobBuilder;
+    use crate::scheduler::types::RecurringInterval;
+    use crate::utils::time::ScheduleTime;
+
+    #[test]
+    fn test_time_until_next_and_since_last_run() {
+        let mut job = JobBuilder::new("test_durations").build();
+        assert!(job.time_until_next().is_none());
+        assert!(job.since_last_run().is_none());
+
+        job = JobBuilder::new("test_durations")
+            .once(ScheduleTime::Delay(Duration::from_secs(60)))
+            .build();
+        let until = job.time_until_next().unwrap();
+        assert!(until > Duration::from_secs(59) && until <= Duration::from_secs(60));
+
+        job.last_run = Some(SystemTime::now() - Duration::from_secs(30));
+        let since = job.since_last_run().unwrap();
+        assert!(since >= Duration::from_secs(30) && since < Duration::from_secs(31));
+
+        // A run time in the past is reported as due now rather than None
+        job.next_run = Some(SystemTime::now() - Duration::from_secs(1));
+        assert_eq!(job.time_until_next(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_next_schedule() {
+        let job = JobBuilder::new("mixed")
+            .recurring(RecurringInterval::Hourly(1), None)
+            .once(ScheduleTime::Delay(Duration::from_secs(60)))
+            .build();
+        let next = job.next_schedule().unwrap();
+        assert!(matches!(next.schedule_type, ScheduleType::Once(_)));
+
+        let mut job = job;
+        job.next_run = Some(SystemTime::now() + Duration::from_secs(10));
+        assert!(job.next_schedule().is_none());
+        assert!(JobBuilder::new("empty").build().next_schedule().is_none());
+    }
+
+    #[test]
+    fn test_state() {
+        let mut job = JobBuilder::new("state")
+            .once(ScheduleTime::Delay(Duration::from_secs(60)))
+            .build();
+        assert_eq!(job.state(), JobState::Active);
+        job.paused = true;
+        assert_eq!(job.state(), JobState::Paused);
+        job.paused = false;
+        job.schedules[0].run_count = 1;
+        job.refresh_next_run();
+        assert_eq!(job.state(), JobState::Completed);
+
+        let follower = JobBuilder::new("follower")
+            .after_job(job.id, Duration::from_secs(5))
+            .build();
+        assert_eq!(follower.state(), JobState::Active);
+    }
+
+    #[test]
+    fn test_schedule_status() {
+        let mut job = JobBuilder::new("status")
+            .once(ScheduleTime::Delay(Duration::from_secs(60)))
+            .build();
+        assert_eq!(job.schedule_status(), ScheduleStatus::Active(job.next_run.unwrap()));
+        job.paused = true;
+        assert_eq!(job.schedule_status(), ScheduleStatus::Paused);
+        job.paused = false;
+        job.schedules[0].run_count = 1;
+        job.refresh_next_run();
+        assert_eq!(job.schedule_status(), ScheduleStatus::Completed);
+
+        let follower = JobBuilder::new("follower")
+            .after_job(job.id, Duration::from_secs(5))
+            .build();
+        assert_eq!(follower.schedule_status(), ScheduleStatus::AwaitingJob(job.id));
+        // 30 February never comes
+        let impossible = JobBuilder::new("impossible").cron("0 0 0 30 2 * *").build();
+        assert_eq!(impossible.schedule_status(), ScheduleStatus::NoUpcomingOccurrence);
+        assert_eq!(JobBuilder::new("unscheduled").build().schedule_status(), ScheduleStatus::Unscheduled);
+    }
+
+    #[test]
+    fn test_run_at() {
+        use std::sync::{Arc, Mutex};
+
+        let started = Arc::new(Mutex::new(None));
+        let seen = Arc::clone(&started);
+        let mut job = JobBuilder::new("clock")
+            .once(ScheduleTime::Delay(Duration::from_secs(60)))
+            .add_handler_with_meta(move |meta| *seen.lock().unwrap() = Some(meta.started))
+            .build();
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        job.run_at(now).unwrap();
+        assert_eq!(*started.lock().unwrap(), Some(now));
+
+        // Executors written before `run_at` still work through it
+        struct Counter(u32);
+        impl JobExecutor for Counter {
+            fn run(&mut self) -> Result<(), JobSchedulerError> {
+                self.0 += 1;
+                Ok(())
+            }
+        }
+        let mut counter = Counter(0);
+        counter.run_at(now).unwrap();
+        assert_eq!(counter.0, 1);
+    }
+
+    #[test]
+    fn test_run_count_aggregation() {
+        let mut job = JobBuilder::new("test_aggregation")
+            .once(ScheduleTime::Delay(Duration::from_secs(5)))
+            .recurring(RecurringInterval::Minutely(1), None)
+            .max_repeat(3)
+            .build();
+        assert_eq!(job.total_run_count(), 0);
+        assert_eq!(job.effective_max_runs(), Some(4));
+
+        job.schedules[0].run_count = 1;
+        job.schedules[1].run_count = 2;
+        assert_eq!(job.total_run_count(), 3);
+
+        // An unbounded schedule makes the whole job unbounded
+        let job = JobBuilder::new("test_aggregation")
+            .recurring(RecurringInterval::Minutely(1), None)
+            .max_repeat(3)
+            .cron("0 0 * * * * *")
+            .build();
+        assert_eq!(job.effective_max_runs(), None);
+    }
+}
@@ -18,6 +18,7 @@
 //!
 //! ```rust
 //! use siafu::{JobBuilder, ScheduleTime, SchedulerError};
+//! use siafu::job::JobExecutor;
 //! use std::time::{Duration, SystemTime};
 //!
 //! fn main() -> Result<(), SchedulerError> {
@@ -50,7 +51,7 @@ pub mod scheduler;
 pub mod error;
 pub mod utils;
 
-pub use job::JobBuilder;
+pub use job::{Job, JobBuilder};
 pub use scheduler::*;
 pub use utils::time::{ScheduleTime, ScheduleTimeError};
 pub use error::Error as SchedulerError;
@@ -1,5 +1,9 @@
 pub mod types;
 pub mod base;
+#[cfg(feature = "ical")]
+pub mod ical;
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
 
 pub use types::*;
 pub use base::*;
\ No newline at end of file
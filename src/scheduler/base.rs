@@ -21,19 +21,29 @@
 //! # Ok::<(), SchedulerError>(())
 //! ```
 
-use std::time::{SystemTime, Duration};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
 use chrono::Utc;
+use chrono_tz::Tz;
+use uuid::Uuid;
 
 use crate::error::Error as JobSchedulerError;
-use crate::job::{JobBuilder, JobExecutor};
-use crate::scheduler::types::{Schedule, ScheduleType, RecurringInterval};
+use crate::job::{Job, JobExecutor, JobSnapshot, JobState, JobTemplate, RunOutcome, RunRecord, ScheduleStatus};
+use crate::job::types::JobHandler;
+use crate::scheduler::types::{DstPolicy, RecurringInterval, RunOffset, Schedule, ScheduleType, TimeWindow, ZonedCronSchedule};
 
 /// Trait defining the behavior of a Scheduler runner.
 pub trait SchedulerRunner {
     /// Add a job to the scheduler.
     ///
-    /// Returns an error if the job has no schedule or no handler.
-    fn add_job(&mut self, job: JobBuilder) -> Result<(), JobSchedulerError>;
+    /// Returns an error if the job has no schedule or no handler, or reuses an existing id.
+    fn add_job(&mut self, job: Job) -> Result<(), JobSchedulerError>;
 
     /// Execute all jobs that are due to run now or earlier.
     fn run_pending(&mut self) -> Result<(), JobSchedulerError>;
@@ -42,277 +52,1674 @@ pub trait SchedulerRunner {
     fn next_run(&self) -> Option<SystemTime>;
 
     /// List all jobs in the scheduler, sorted by next run time.
-    fn list_all_jobs(&self) -> Vec<&JobBuilder>;
+    fn list_all_jobs(&self) -> Vec<&Job>;
+}
+
+/// Default upper bound on how long the run loop sleeps before re-checking its jobs.
+pub const DEFAULT_MAX_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Default number of run records kept per job.
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+// Scheduler-wide fallback for job failures, receiving the failing job's id
+type ErrorHandler = Box<dyn Fn(Uuid, &JobSchedulerError) + Send + 'static>;
+// Called when the run loop runs out of jobs to run
+type IdleHandler = Box<dyn Fn() + Send + 'static>;
+
+/// Field to order jobs by in `Scheduler::list_jobs_sorted_by`.
+///
+/// Keys sort ascending, except `Priority`, with jobs missing the value last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    /// Next run time, earliest first. This is the order of `list_all_jobs`.
+    #[default]
+    NextRun,
+    /// Job name, with unnamed jobs last.
+    Name,
+    /// Time of the last successful run, with jobs that have not run last.
+    LastRun,
+    /// Total runs across all of a job's schedules.
+    RunCount,
+    /// Priority set with `JobBuilder::priority`, highest first.
+    Priority,
+}
+
+/// Record of which runs have fired, consulted for jobs built with
+/// `JobBuilder::idempotency_key`.
+///
+/// Back it with storage shared between processes, such as a database table with a unique
+/// key, so that schedulers running the same jobs fire each run only once between them. A
+/// `HashSet<String>` works as a store within one process.
+pub trait DedupStore: Send {
+    /// Claim `key` for a run about to fire. Returns false if it was already claimed, in
+    /// which case the run is skipped.
+    fn claim(&mut self, key: &str) -> bool;
+}
+
+impl DedupStore for HashSet<String> {
+    fn claim(&mut self, key: &str) -> bool {
+        self.insert(key.to_string())
+    }
 }
 
 /// Scheduler implementation for managing and executing jobs.
 pub struct Scheduler {
-    jobs: Vec<JobBuilder>,
+    jobs: Vec<Job>,
+    error_handler: Option<ErrorHandler>,
+    idle_handler: Option<IdleHandler>,
+    // Handlers still running on worker threads, keyed by job id
+    in_flight: Vec<(Uuid, JoinHandle<()>)>,
+    max_poll_interval: Duration,
+    // Longest stretch of missed runs replayed before the rest are skipped
+    max_catchup: Option<Duration>,
+    // Most jobs the scheduler will hold
+    max_jobs: Option<usize>,
+    // Consecutive panics after which a job is paused
+    panic_threshold: Option<u32>,
+    // Granularity times are rounded down to before deciding whether a run is due
+    time_resolution: Option<Duration>,
+    // Zone plain cron schedules of newly added jobs are matched in
+    default_timezone: Option<Tz>,
+    // Shortest recurring interval a newly added job may use
+    min_interval: Duration,
+    // Keys of runs already fired, for jobs with an idempotency key
+    dedup_store: Option<Box<dyn DedupStore>>,
+    history_limit: usize,
+    completion_sender: Sender<Completion>,
+    completion_receiver: Receiver<Completion>,
+    job_sender: Sender<Job>,
+    job_receiver: Receiver<Job>,
+}
+
+// Outcome of a run on a dedicated thread, handing the handler back to its job
+struct Completion {
+    id: Uuid,
+    started: SystemTime,
+    handler: JobHandler,
+    context: Option<Box<dyn Any + Send>>,
+    // `Err` if the handler panicked
+    result: thread::Result<Result<(), JobSchedulerError>>,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cloneable handle for adding jobs to a `Scheduler` from other threads.
+///
+/// Jobs sent through a handle are picked up by the run loop immediately, waking it
+/// early if it is sleeping until a later job.
+#[derive(Clone)]
+pub struct SchedulerHandle {
+    job_sender: Sender<Job>,
+}
+
+impl SchedulerHandle {
+    /// Send a job to the scheduler.
+    ///
+    /// Returns an error if the job is missing a schedule or handler, or if the scheduler has been dropped.
+    /// A job the scheduler then cannot add, e.g. because its id is taken, is reported to the
+    /// job's or the scheduler's error handler and dropped.
+    pub fn add_job(&self, job: Job) -> Result<(), JobSchedulerError> {
+        job.ensure_runnable()?;
+        self.job_sender
+            .send(job)
+            .map_err(|_| JobSchedulerError::ExecutionFailed("scheduler is no longer running".to_string()))
+    }
 }
 
 impl Scheduler {
     /// Create a new, empty Scheduler.
     pub fn new() -> Self {
-        Self { jobs: Vec::new() }
+        let (job_sender, job_receiver) = mpsc::channel();
+        let (completion_sender, completion_receiver) = mpsc::channel();
+        Self {
+            jobs: Vec::new(),
+            error_handler: None,
+            idle_handler: None,
+            in_flight: Vec::new(),
+            max_poll_interval: DEFAULT_MAX_POLL_INTERVAL,
+            max_catchup: None,
+            max_jobs: None,
+            panic_threshold: None,
+            time_resolution: None,
+            default_timezone: None,
+            min_interval: Duration::ZERO,
+            dedup_store: None,
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            completion_sender,
+            completion_receiver,
+            job_sender,
+            job_receiver,
+        }
+    }
+
+    /// Create a new, empty Scheduler with room for `n` jobs before reallocating.
+    pub fn with_capacity(n: usize) -> Self {
+        let mut scheduler = Self::new();
+        scheduler.jobs.reserve_exact(n);
+        scheduler
+    }
+
+    /// Create a new, empty Scheduler that holds at most `n` jobs.
+    ///
+    /// Once full, adding a job fails with `InvalidSchedule("capacity exceeded")`, which keeps
+    /// systems that add jobs from external input from accumulating them without bound.
+    pub fn with_max_jobs(n: usize) -> Self {
+        let mut scheduler = Self::new();
+        scheduler.max_jobs = Some(n);
+        scheduler
+    }
+
+    /// Reserve room for at least `additional` more jobs, e.g. before adding many at once.
+    pub fn reserve(&mut self, additional: usize) {
+        self.jobs.reserve(additional);
+    }
+
+    /// Set the longest time the run loop will sleep before re-checking its jobs.
+    ///
+    /// The loop normally sleeps exactly until the next job is due; this caps that sleep.
+    pub fn max_poll_interval(mut self, interval: Duration) -> Self {
+        self.max_poll_interval = interval;
+        self
+    }
+
+    /// Cap how much missed time is caught up on when recurring jobs fall behind.
+    ///
+    /// A recurring job that falls behind, e.g. because the system clock jumped forward, fires
+    /// its missed runs one per sweep. With a cap, runs missed by more than `limit` are skipped
+    /// with a warning instead, so only the most recent `limit` of missed runs are replayed.
+    /// By default every missed run is replayed.
+    pub fn max_catchup(mut self, limit: Duration) -> Self {
+        self.max_catchup = Some(limit);
+        self
+    }
+
+    /// Set how many run records each job keeps, evicting the oldest beyond that.
+    ///
+    /// Bounds the memory used by `Job::recent_runs` in long-lived processes. Defaults to
+    /// `DEFAULT_HISTORY_LIMIT`.
+    pub fn history_limit(mut self, n: usize) -> Self {
+        self.history_limit = n;
+        self
+    }
+
+    /// Pause a job once its handler has panicked `n` times in a row.
+    ///
+    /// Stops a crash-looping job from spinning. When the job is paused its error handlers
+    /// are called with a "circuit open" `ExecutionFailed` error, and it stays paused until
+    /// `resume_job` is called.
+    pub fn panic_threshold(mut self, n: u32) -> Self {
+        self.panic_threshold = Some(n);
+        self
+    }
+
+    /// Round times down to a multiple of `resolution` when deciding whether a run is due.
+    ///
+    /// On platforms where the clock is coarse, runs scheduled a hair apart can fall either
+    /// side of a sweep's reading of the clock. Rounding both the sweep time and each run time
+    /// makes runs within the same step due together, firing in the order the jobs were
+    /// added. Runs may then start up to `resolution` early. By default times are compared
+    /// exactly.
+    pub fn time_resolution(mut self, resolution: Duration) -> Self {
+        self.time_resolution = Some(resolution).filter(|resolution| !resolution.is_zero());
+        self
+    }
+
+    /// Match the plain `cron` schedules of jobs added from now on against local time in
+    /// `timezone` rather than UTC.
+    ///
+    /// Precedence is per job, then the scheduler, then UTC: schedules built with `cron_tz`
+    /// keep their own zone, and jobs added before the default was set stay on UTC. Other
+    /// cron-based schedules, such as `cron_every_nth`, are unaffected.
+    pub fn default_timezone(mut self, timezone: Tz) -> Self {
+        self.default_timezone = Some(timezone);
+        self
+    }
+
+    /// Reject jobs added from now on whose recurring schedules repeat more often than every
+    /// `interval`, such as an accidental `Secondly(0)` that would run in a tight loop.
+    ///
+    /// `add_job` returns `InvalidSchedule` for such jobs. Defaults to zero, allowing any
+    /// interval.
+    pub fn min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    /// Check the runs of jobs built with `idempotency_key` against `store` before they fire.
+    pub fn dedup_store<S: DedupStore + 'static>(mut self, store: S) -> Self {
+        self.dedup_store = Some(Box::new(store));
+        self
+    }
+
+    /// Set a global handler for job failures.
+    ///
+    /// When a job fails in `run_pending`, its own `on_error` handler is used if it has one,
+    /// otherwise this handler is called with the job's id and the error. If neither is set
+    /// the error is returned from `run_pending`.
+    pub fn on_any_error<F>(&mut self, f: F)
+    where F: Fn(Uuid, &JobSchedulerError) + Send + 'static {
+        self.error_handler = Some(Box::new(f));
+    }
+
+    /// Set a handler called when `run_non_blocking` runs out of jobs.
+    ///
+    /// Called once per run loop, when no job has an upcoming run and every run on a
+    /// dedicated thread has finished, just before the loop returns. Useful for logging or
+    /// shutting down at the natural end of a batch of one-off jobs.
+    pub fn on_idle<F>(&mut self, f: F)
+    where F: Fn() + Send + 'static {
+        self.idle_handler = Some(Box::new(f));
+    }
+
+    /// Return a handle that can add jobs to this scheduler while it is running.
+    pub fn handle(&self) -> SchedulerHandle {
+        SchedulerHandle { job_sender: self.job_sender.clone() }
     }
 
     /// Add a job to the scheduler.
     ///
-    /// Returns an error if the job is missing a schedule or handler.
-    pub fn add_job(&mut self, job: JobBuilder) -> Result<(), JobSchedulerError> {
-        if job.schedules.is_empty() {
-            return Err(JobSchedulerError::MissingSchedule);
-        }
-        if job.handler.is_none() {
-            return Err(JobSchedulerError::HandlerNotBuilt);
+    /// Returns an error if the job is missing a schedule or handler, if a job with the same
+    /// id has already been added, or if the scheduler is at its job limit.
+    pub fn add_job(&mut self, job: Job) -> Result<(), JobSchedulerError> {
+        job.ensure_runnable()?;
+        self.insert_job(job)
+    }
+
+    /// Add a job that has no handler yet, to be bound later with `replace_handler`.
+    ///
+    /// Lets jobs be staged from configuration before their handlers are available. The job
+    /// is checked as in `add_job` apart from its handler, and is left out of sweeps and
+    /// `next_run` until a handler is attached.
+    pub fn add_unbound_job(&mut self, mut job: Job) -> Result<(), JobSchedulerError> {
+        job.ensure_schedulable()?;
+        job.unbound = job.handler.is_none();
+        self.insert_job(job)
+    }
+
+    // Add a checked job, unless there is no room or its id is taken
+    fn insert_job(&mut self, job: Job) -> Result<(), JobSchedulerError> {
+        self.ensure_insertable(std::slice::from_ref(&job))?;
+        self.push_job(job);
+        Ok(())
+    }
+
+    // Check that `jobs` can all be added: none repeats too fast, reuses an id already here or
+    // earlier in `jobs`, or takes the scheduler past its job limit
+    fn ensure_insertable(&self, jobs: &[Job]) -> Result<(), JobSchedulerError> {
+        for (index, job) in jobs.iter().enumerate() {
+            self.ensure_min_interval(job)?;
+            if self.jobs.iter().chain(&jobs[..index]).any(|existing| existing.id == job.id) {
+                return Err(JobSchedulerError::InvalidSchedule(format!("duplicate job id: {}", job.id)));
+            }
         }
+        self.ensure_capacity(jobs.len())
+    }
+
+    // Add a job that passed `ensure_insertable`
+    fn push_job(&mut self, mut job: Job) {
+        self.apply_default_timezone(&mut job);
         self.jobs.push(job);
+    }
+
+    // Check that none of a new job's recurring schedules repeat faster than the minimum interval
+    fn ensure_min_interval(&self, job: &Job) -> Result<(), JobSchedulerError> {
+        for sched in &job.schedules {
+            if let ScheduleType::Recurring(recurring) = &sched.schedule_type
+                && recurring.interval.delta() < self.min_interval
+            {
+                return Err(JobSchedulerError::InvalidSchedule(format!(
+                    "interval of {:?} is below the minimum of {:?}",
+                    recurring.interval.delta(),
+                    self.min_interval
+                )));
+            }
+        }
         Ok(())
     }
 
-    /// Run all pending jobs and update their schedules.
-    pub fn run_pending(&mut self) -> Result<(), JobSchedulerError> {
+    // Move a new job's plain cron schedules into the default timezone, if one is set
+    fn apply_default_timezone(&self, job: &mut Job) {
+        let Some(timezone) = self.default_timezone else {
+            return;
+        };
         let now = SystemTime::now();
-        for job in self.jobs.iter_mut() {
-            if let Some(next) = job.next_run {
-                if next <= now {
-                    job.run()?;
-                    job.last_run = Some(now);
-                    // update each schedule that fired
-                    for sched in job.schedules.iter_mut() {
-                        if let Some(rn) = Self::peek_next_run(sched) {
-                            if rn <= now {
-                                sched.run_count += 1;
-                                Self::compute_next_run(sched);
-                            }
-                        }
+        let mut moved = false;
+        for sched in job.schedules.iter_mut() {
+            if let ScheduleType::Cron { source, schedule } = &sched.schedule_type {
+                let zoned = ZonedCronSchedule {
+                    source: source.clone(),
+                    schedule: schedule.clone(),
+                    timezone,
+                    dst_policy: DstPolicy::default(),
+                };
+                if sched.pending_run().is_some() {
+                    sched.next_run = zoned.next_after(now);
+                    if let Some(window) = job.active_window {
+                        window.constrain(sched);
                     }
-                    // recompute earliest next_run across schedules
-                    job.next_run = job.schedules.iter()
-                        .filter_map(|s| Self::peek_next_run(s))
-                        .min();
                 }
+                sched.schedule_type = ScheduleType::ZonedCron(zoned);
+                moved = true;
+            }
+        }
+        if moved {
+            job.refresh_next_run();
+        }
+    }
+
+    /// Add several jobs at once, returning their ids in order.
+    ///
+    /// All or nothing: every job is checked as in `add_job`, including for ids repeated
+    /// within `jobs`, and if any check fails none of the jobs are added.
+    pub fn add_jobs(&mut self, jobs: Vec<Job>) -> Result<Vec<Uuid>, JobSchedulerError> {
+        for job in &jobs {
+            job.ensure_runnable()?;
+        }
+        self.ensure_insertable(&jobs)?;
+        let ids = jobs.iter().map(|job| job.id).collect();
+        for job in jobs {
+            self.push_job(job);
+        }
+        Ok(ids)
+    }
+
+    /// Move every job from `other` into this scheduler.
+    ///
+    /// All or nothing: the jobs are checked as in `add_job`, apart from their handlers, and
+    /// `InvalidSchedule` is returned without moving any if one fails, e.g. by having the same
+    /// id as a job already here. Runs still going on `other`'s dedicated threads are waited
+    /// for first so their outcomes are recorded; failures among them are kept in the jobs'
+    /// history and logged rather than returned, as the jobs still move. `other`'s own
+    /// settings and error handler are dropped, and this scheduler's default timezone applies
+    /// to the moved jobs.
+    pub fn merge(&mut self, mut other: Scheduler) -> Result<(), JobSchedulerError> {
+        other.receive_jobs();
+        while !other.in_flight.is_empty() {
+            thread::sleep(Duration::from_millis(10));
+            if let Err(err) = other.collect_completions() {
+                log::warn!("Run finished with an error while merging schedulers: {}", err);
             }
         }
+        self.ensure_insertable(&other.jobs)?;
+        for job in other.jobs.drain(..) {
+            self.push_job(job);
+        }
         Ok(())
     }
 
-    /// Return the next scheduled run time among all jobs.
-    pub fn next_run(&self) -> Option<SystemTime> {
-        self.jobs.iter().filter_map(|job| job.next_run).min()
+    // Check there is room for `additional` more jobs under the job limit
+    fn ensure_capacity(&self, additional: usize) -> Result<(), JobSchedulerError> {
+        if self.max_jobs.is_some_and(|max| self.jobs.len() + additional > max) {
+            return Err(JobSchedulerError::InvalidSchedule("capacity exceeded".to_string()));
+        }
+        Ok(())
     }
 
-    /// Return a list of all jobs sorted by next run time (earliest first).
-    pub fn list_all_jobs(&self) -> Vec<&JobBuilder> {
-        // Return jobs sorted by next_run ascending, jobs with no next_run at the end
-        let mut job_refs: Vec<&JobBuilder> = self.jobs.iter().collect();
-        job_refs.sort_by(|a, b| match (a.next_run, b.next_run) {
-            (Some(a_time), Some(b_time)) => a_time.cmp(&b_time),
-            (Some(_), None) => std::cmp::Ordering::Less,
-            (None, Some(_)) => std::cmp::Ordering::Greater,
-            (None, None) => std::cmp::Ordering::Equal,
-        });
-        job_refs
+    /// Create and add a job from `template` for each of `names`, returning their ids in order.
+    ///
+    /// Each job's handler is made by calling `handler_factory` with its name. The jobs are
+    /// added as with `add_jobs`, so if any fails validation, none of them are added.
+    pub fn add_from_template<F, H>(
+        &mut self,
+        template: &JobTemplate,
+        names: &[&str],
+        handler_factory: F,
+    ) -> Result<Vec<Uuid>, JobSchedulerError>
+    where
+        F: Fn(&str) -> H,
+        H: Fn() + Send + 'static,
+    {
+        let jobs: Vec<Job> = names.iter()
+            .map(|name| template.instantiate(name).add_handler(handler_factory(name)).build())
+            .collect();
+        self.add_jobs(jobs)
     }
 
-    /// Continuously run pending jobs without busy-waiting, sleeping until the next job is due.
-    pub fn run_non_blocking(&mut self) -> Result<(), JobSchedulerError> {
-        loop {
-            if let Some(next) = self.next_run() {
-                let now = SystemTime::now();
-                if next > now {
-                    let duration = next.duration_since(now).unwrap_or_else(|_| Duration::from_secs(0));
-                    std::thread::sleep(duration);
+    /// Bind a new handler to the job with the given id, replacing any existing handler.
+    ///
+    /// Handlers cannot be persisted, so this is how jobs restored from saved state are made
+    /// runnable again, and how jobs staged with `add_unbound_job` get their handler.
+    /// Returns `JobNotFound` if no job has the id.
+    pub fn replace_handler(&mut self, id: Uuid, handler: impl Fn() + Send + 'static) -> Result<(), JobSchedulerError> {
+        let job = self.jobs.iter_mut()
+            .find(|job| job.id == id)
+            .ok_or_else(|| JobSchedulerError::JobNotFound(id.to_string()))?;
+        job.handler = Some(Box::new(move |_, _| {
+            handler();
+            Ok(())
+        }));
+        job.unbound = false;
+        Ok(())
+    }
+
+    /// Remove and return the values produced by the job with the given id, oldest first.
+    ///
+    /// Values come from handlers set with `JobBuilder::add_handler_returning` and can be
+    /// downcast to the handler's return type. Returns `JobNotFound` if no job has the id.
+    pub fn take_results(&mut self, id: Uuid) -> Result<Vec<Box<dyn Any + Send>>, JobSchedulerError> {
+        let job = self.jobs.iter()
+            .find(|job| job.id == id)
+            .ok_or_else(|| JobSchedulerError::JobNotFound(id.to_string()))?;
+        let mut results = job.results.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        Ok(results.drain(..).collect())
+    }
+
+    /// Stop running the job with the given id until `resume_job` is called.
+    ///
+    /// Returns `JobNotFound` if no job has the id.
+    pub fn pause_job(&mut self, id: Uuid) -> Result<(), JobSchedulerError> {
+        let job = self.jobs.iter_mut()
+            .find(|job| job.id == id)
+            .ok_or_else(|| JobSchedulerError::JobNotFound(id.to_string()))?;
+        job.paused = true;
+        Ok(())
+    }
+
+    /// Resume the paused job with the given id, also resetting its count of consecutive panics.
+    ///
+    /// Runs missed while paused are caught up as for any overdue job. Returns `JobNotFound`
+    /// if no job has the id.
+    pub fn resume_job(&mut self, id: Uuid) -> Result<(), JobSchedulerError> {
+        let job = self.jobs.iter_mut()
+            .find(|job| job.id == id)
+            .ok_or_else(|| JobSchedulerError::JobNotFound(id.to_string()))?;
+        job.paused = false;
+        job.consecutive_panics = 0;
+        Ok(())
+    }
+
+    /// Run the job with the given id at `time` instead of its next scheduled run.
+    ///
+    /// The job's schedules are left untouched, so after the overridden run it resumes its
+    /// normal cadence. Returns `JobNotFound` if no job has the id.
+    pub fn set_next_run(&mut self, id: Uuid, time: SystemTime) -> Result<(), JobSchedulerError> {
+        let job = self.jobs.iter_mut()
+            .find(|job| job.id == id)
+            .ok_or_else(|| JobSchedulerError::JobNotFound(id.to_string()))?;
+        job.next_run = Some(time);
+        Ok(())
+    }
+
+    /// Move the timezone-aware cron schedules of the job with the given id to `timezone`.
+    ///
+    /// Their next runs are recomputed from now in the new zone, e.g. after a tenant changes
+    /// locale. Returns `JobNotFound` if no job has the id, or `InvalidSchedule` if the job
+    /// has no schedule built with `cron_tz`.
+    pub fn set_job_timezone(&mut self, id: Uuid, timezone: Tz) -> Result<(), JobSchedulerError> {
+        let job = self.jobs.iter_mut()
+            .find(|job| job.id == id)
+            .ok_or_else(|| JobSchedulerError::JobNotFound(id.to_string()))?;
+        let now = SystemTime::now();
+        let mut found = false;
+        for sched in job.schedules.iter_mut() {
+            let exhausted = sched.max_runs.is_some_and(|max| sched.run_count >= max);
+            if let ScheduleType::ZonedCron(zoned) = &mut sched.schedule_type {
+                zoned.timezone = timezone;
+                if !exhausted {
+                    sched.next_run = zoned.next_after(now);
+                    if let Some(window) = job.active_window {
+                        window.constrain(sched);
+                    }
                 }
-                self.run_pending()?;
-            } else {
-                break;
+                found = true;
             }
         }
+        if !found {
+            return Err(JobSchedulerError::InvalidSchedule(format!("job {} has no timezone-aware cron schedule", id)));
+        }
+        job.refresh_next_run();
         Ok(())
     }
 
-    fn compute_next_run(schedule: &mut Schedule) -> Option<SystemTime> {
-        if let Some(max_runs) = schedule.max_runs {
-            if schedule.run_count >= max_runs {
-                return None;
+    /// Change the run limit of every schedule of the job with the given id, `None` removing it.
+    ///
+    /// Raising the limit of a completed job resumes it from its next occurrence after now;
+    /// recurring schedules skip the runs missed while it was complete, and one-off schedules
+    /// that already ran stay finished. Returns `JobNotFound` if no job has the id.
+    pub fn set_max_runs(&mut self, id: Uuid, max: Option<u32>) -> Result<(), JobSchedulerError> {
+        let job = self.jobs.iter_mut()
+            .find(|job| job.id == id)
+            .ok_or_else(|| JobSchedulerError::JobNotFound(id.to_string()))?;
+        let now = SystemTime::now();
+        for sched in job.schedules.iter_mut() {
+            sched.max_runs = max;
+            // A schedule that stopped at its old limit has no next run yet
+            if sched.next_run.is_none() && max.is_none_or(|max| sched.run_count < max) {
+                sched.advance_at(now);
+                Self::skip_runs_before(sched, now);
+                if let Some(window) = job.active_window {
+                    window.constrain(sched);
+                }
             }
         }
+        job.refresh_next_run();
+        Ok(())
+    }
 
-        match &mut schedule.schedule_type {
-            ScheduleType::Once(_time) => None, // Runs once, no next run
-            ScheduleType::Random(_) => None, // Runs once at the pre-calculated time, no next run
-            ScheduleType::Recurring(recurring) => {
-                // calculate delta based on interval
-                let delta = match &recurring.interval {
-                    RecurringInterval::Secondly(secs) => {
-                        Duration::from_secs(*secs as u64)
-                    },
-                    RecurringInterval::Minutely(mins) => {
-                        Duration::from_secs(60 * *mins as u64)
-                    },
-                    RecurringInterval::Hourly(hours) => {
-                        Duration::from_secs(3600 * *hours as u64)
-                    },
-                    RecurringInterval::Daily(days) => {
-                        Duration::from_secs(86400 * *days as u64)
-                    },
-                    RecurringInterval::Weekly(weeks) => {
-                        Duration::from_secs(7 * 86400 * *weeks as u64)
-                    },
-                    RecurringInterval::Monthly(months) => {
-                        Duration::from_secs(30 * 86400 * *months as u64)
-                    },
-                    RecurringInterval::Custom { expression, frequency } => {
-                        let days = match expression.as_str() {
-                            "daily" => 1,
-                            "weekly" => 7,
-                            "monthly" => 30,
-                            _ => *frequency,
-                        };
-                        Duration::from_secs(days as u64 * 86400)
-                    },
-                };
-                // update next_run
-                let next = recurring.next_run + delta;
-                recurring.next_run = next;
-                Some(next)
+    /// Suppress runs of the job with the given id until `until`, after which it resumes its
+    /// normal schedule.
+    ///
+    /// Recurring occurrences falling before `until` are skipped rather than caught up; other
+    /// schedules that were due in the meantime run once at `until`. Does nothing if the job's
+    /// next run is already at or after `until`. Returns `JobNotFound` if no job has the id.
+    pub fn snooze(&mut self, id: Uuid, until: SystemTime) -> Result<(), JobSchedulerError> {
+        let job = self.jobs.iter_mut()
+            .find(|job| job.id == id)
+            .ok_or_else(|| JobSchedulerError::JobNotFound(id.to_string()))?;
+        if job.next_run.is_none_or(|next| next >= until) {
+            return Ok(());
+        }
+        for sched in job.schedules.iter_mut() {
+            Self::skip_runs_before_within(sched, until, job.active_window);
+        }
+        job.refresh_next_run();
+        if job.next_run.is_some_and(|next| next < until) {
+            job.next_run = Some(until);
+        }
+        Ok(())
+    }
+
+    /// Stop the scheduler, waiting up to `timeout` for handlers still running on worker threads.
+    ///
+    /// Consuming the scheduler means no further runs are started, and jobs sent through a
+    /// `SchedulerHandle` afterwards are rejected. Returns `ExecutionFailed` listing the ids of
+    /// jobs whose handlers had not finished when the timeout expired.
+    pub fn shutdown(mut self, timeout: Duration) -> Result<(), JobSchedulerError> {
+        let deadline = SystemTime::now() + timeout;
+        loop {
+            self.in_flight.retain(|(_, worker)| !worker.is_finished());
+            if self.in_flight.is_empty() {
+                return Ok(());
             }
-            ScheduleType::Cron(cron_schedule) => {
-                // let now = Utc::now();
-                cron_schedule.upcoming(Utc).next().map(|dt| dt.into())
+            let now = SystemTime::now();
+            if now >= deadline {
+                break;
             }
+            let remaining = deadline.duration_since(now).unwrap_or_default();
+            std::thread::sleep(remaining.min(Duration::from_millis(10)));
         }
+        let unfinished: Vec<String> = self.in_flight.iter().map(|(id, _)| id.to_string()).collect();
+        Err(JobSchedulerError::ExecutionFailed(format!(
+            "jobs did not finish before shutdown timeout: {}",
+            unfinished.join(", ")
+        )))
     }
 
-    // Helper to peek next run for a schedule without mutating it
-    fn peek_next_run(schedule: &Schedule) -> Option<SystemTime> {
-        // respect max_runs
-        if let Some(max) = schedule.max_runs {
-            if schedule.run_count >= max {
-                return None;
-            }
+    // Move any jobs sent through a `SchedulerHandle` into the scheduler
+    fn receive_jobs(&mut self) {
+        while let Ok(job) = self.job_receiver.try_recv() {
+            self.accept_sent_job(job);
         }
-        match &schedule.schedule_type {
-            ScheduleType::Once(_) => None,
-            ScheduleType::Random(_) => None,
-            ScheduleType::Recurring(rec) => Some(rec.next_run),
-            ScheduleType::Cron(cron_schedule) => cron_schedule.upcoming(Utc).next().map(|dt| dt.into()),
+    }
+
+    // Add a job sent through a `SchedulerHandle`. The sender has moved on, so a job that
+    // cannot be added is reported to the error handlers and dropped rather than stopping
+    // the run loop.
+    fn accept_sent_job(&mut self, job: Job) {
+        let checked = job.ensure_runnable().and_then(|()| self.ensure_insertable(std::slice::from_ref(&job)));
+        match checked {
+            Ok(()) => self.push_job(job),
+            Err(err) => {
+                log::warn!("Dropped job {} sent through a handle: {}", job.id, err);
+                // Without a handler to report to, the log is all there is
+                let _ = Self::report_error(&job, self.error_handler.as_ref(), err);
+            }
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::scheduler::types::{RecurringSchedule, RecurringInterval};
-    use crate::utils::time::ScheduleTime;
+    /// Run all pending jobs and update their schedules.
+    ///
+    /// A failing job is passed to its `on_error` handler, or the scheduler's `on_any_error`
+    /// handler, and its schedule advances as if it had run. Without either handler the error
+    /// is returned and the job stays due. A handler that panics fails with `ExecutionFailed`.
+    /// The rest of the due jobs still run after a failure; if several fail without a handler
+    /// their errors are combined into one `ExecutionFailed`.
+    ///
+    /// Jobs built with `dedicated_thread` are started on their own thread instead; their
+    /// schedule advances when the run starts, and the outcome is recorded by a later sweep
+    /// once the run has finished.
+    pub fn run_pending(&mut self) -> Result<(), JobSchedulerError> {
+        self.run_pending_at(SystemTime::now())
+    }
 
-    use super::*;
-    use std::thread::sleep;
+    /// Run due jobs once and return how long to wait before calling `tick` again.
+    ///
+    /// A pull-based alternative to `run_non_blocking` for driving the scheduler from an
+    /// external event loop. The wait is capped at the max poll interval so jobs sent through
+    /// a `SchedulerHandle` are picked up. Returns `None` once no jobs have an upcoming run
+    /// and none are still running.
+    pub fn tick(&mut self) -> Result<Option<Duration>, JobSchedulerError> {
+        self.receive_jobs();
+        self.run_pending()?;
+        let wait = match self.next_wake(SystemTime::now()) {
+            Some(next) => next.duration_since(SystemTime::now()).unwrap_or(Duration::ZERO),
+            // Check back for the outcome of runs still going on dedicated threads
+            None if !self.in_flight.is_empty() => self.max_poll_interval,
+            None => return Ok(None),
+        };
+        Ok(Some(wait.min(self.max_poll_interval)))
+    }
 
+    /// Run all jobs due at `now`, treating it as the current time.
+    ///
+    /// Behaves like `run_pending` but uses the supplied time to decide which jobs are due and
+    /// to advance their schedules, so tests can step through time without sleeping.
+    pub fn run_pending_at(&mut self, now: SystemTime) -> Result<(), JobSchedulerError> {
+        let sweep_started = Instant::now();
+        // Failed runs from earlier sweeps don't hold up this one
+        let mut errors: Vec<_> = self.collect_completions().err().into_iter().collect();
+        self.limit_catchup(now);
+        self.defer_opportunistic(now);
+        for index in 0..self.jobs.len() {
+            if !self.is_due(index, now) {
+                continue;
+            }
+            let skipped = self.skips_run(index, now);
+            let job = &mut self.jobs[index];
+            if skipped {
+                job.record_run(RunRecord { started: now, outcome: RunOutcome::Skipped }, self.history_limit);
+            } else {
+                // Earlier jobs in the sweep delay this one past `now`
+                job.record_drift(now + sweep_started.elapsed(), self.history_limit);
+                if job.dedicated_thread {
+                    if let Err(err) = Self::spawn_dedicated(job, now, &self.completion_sender, &mut self.in_flight)
+                        && let Err(err) = Self::record_outcome(job, self.error_handler.as_ref(), self.history_limit, now, Err(err))
+                    {
+                        errors.push(err);
+                        continue;
+                    }
+                } else {
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| job.run_at(now)));
+                    match self.settle_run(index, now, now, result) {
+                        // The schedule stays put until the last attempt
+                        Ok(true) => continue,
+                        Ok(false) => {}
+                        // The job stays due, but the rest of the sweep still runs
+                        Err(err) => {
+                            errors.push(err);
+                            continue;
+                        }
+                    }
+                }
+            }
+            self.finish_run(index, now, sweep_started, skipped);
+        }
+        combine_errors(errors)
+    }
 
-    // Helper function for tests
-    fn dummy_handler() {
-        // Removed Ok(())
+    /// Run all pending jobs like `run_pending`, but with each due handler on its own thread.
+    ///
+    /// The handlers run concurrently and are all joined before any schedule is updated, so a
+    /// slow job no longer holds up the others in the sweep. Failures are handled as in
+    /// `run_pending`.
+    pub fn run_pending_parallel(&mut self) -> Result<(), JobSchedulerError> {
+        self.run_pending_parallel_at(SystemTime::now())
     }
-    
-    #[test]
-    fn test_new_scheduler_empty() {
-        let scheduler = Scheduler::new();
-        assert_eq!(scheduler.jobs.len(), 0);
-        assert_eq!(scheduler.next_run(), None);
-        assert_eq!(scheduler.list_all_jobs().len(), 0);
+
+    // `run_pending_parallel` treating `now` as the current time
+    fn run_pending_parallel_at(&mut self, now: SystemTime) -> Result<(), JobSchedulerError> {
+        let sweep_started = Instant::now();
+        let mut errors: Vec<_> = self.collect_completions().err().into_iter().collect();
+        self.limit_catchup(now);
+        self.defer_opportunistic(now);
+        let mut ready = Vec::new();
+        for index in 0..self.jobs.len() {
+            if !self.is_due(index, now) {
+                continue;
+            }
+            let skipped = self.skips_run(index, now);
+            let job = &mut self.jobs[index];
+            if skipped {
+                job.record_run(RunRecord { started: now, outcome: RunOutcome::Skipped }, self.history_limit);
+                self.finish_run(index, now, sweep_started, true);
+            } else if job.dedicated_thread {
+                job.record_drift(now + sweep_started.elapsed(), self.history_limit);
+                if let Err(err) = Self::spawn_dedicated(job, now, &self.completion_sender, &mut self.in_flight)
+                    && let Err(err) = Self::record_outcome(job, self.error_handler.as_ref(), self.history_limit, now, Err(err))
+                {
+                    errors.push(err);
+                    continue;
+                }
+                self.finish_run(index, now, sweep_started, false);
+            } else {
+                ready.push(index);
+            }
+        }
+        // The threads all start together, once the jobs before them have been dispatched
+        let started = now + sweep_started.elapsed();
+        for &index in &ready {
+            self.jobs[index].record_drift(started, self.history_limit);
+        }
+        let results: Vec<_> = thread::scope(|scope| {
+            let workers: Vec<_> = self.jobs
+                .iter_mut()
+                .enumerate()
+                .filter(|(index, _)| ready.contains(index))
+                .map(|(index, job)| (index, scope.spawn(move || job.run_at(now))))
+                .collect();
+            workers.into_iter().map(|(index, worker)| (index, worker.join())).collect()
+        });
+        for (index, result) in results {
+            match self.settle_run(index, now, now, result) {
+                Ok(true) => {}
+                Ok(false) => self.finish_run(index, now, sweep_started, false),
+                Err(err) => errors.push(err),
+            }
+        }
+        combine_errors(errors)
     }
-    
-    #[test]
-    fn test_add_job() -> Result<(), JobSchedulerError> {
-        let mut scheduler = Scheduler::new();
-        let job = JobBuilder::new("test-job")
-            .once(ScheduleTime::At(SystemTime::now() + Duration::from_secs(60)))
-            .add_handler(dummy_handler)
-            .build();
-            
-        scheduler.add_job(job)?;
-        assert_eq!(scheduler.jobs.len(), 1);
-        assert_eq!(scheduler.list_all_jobs().len(), 1);
-        
-        let job_ref = scheduler.list_all_jobs()[0];
-        assert_eq!(job_ref.name, Some("test-job".to_string()));
-        
-        Ok(())
+
+    // Whether the job at `index` should start a run at `now`
+    fn is_due(&self, index: usize, now: SystemTime) -> bool {
+        let job = &self.jobs[index];
+        self.dependencies_met(job)
+            && !job.paused
+            // A job's previous run must finish before it can start another
+            && !self.in_flight.iter().any(|(id, _)| *id == job.id)
+            && !job.unbound
+            && (job.next_run.is_some_and(|next| due_by(next, now, self.time_resolution))
+                || job.schedules.iter().any(|sched| {
+                    matches!(sched.schedule_type, ScheduleType::Immediate) && sched.pending_run().is_some()
+                }))
     }
-    
-    #[test]
-    fn test_add_job_no_schedule() {
-        let mut scheduler = Scheduler::new();
-        // Create a job without any schedules
-        let job = JobBuilder::new("no-schedule")
-            .add_handler(dummy_handler)
-            .build();
-            
-        let result = scheduler.add_job(job);
-        assert!(result.is_err());
-        match result {
-            Err(JobSchedulerError::MissingSchedule) => {},
-            _ => panic!("Expected MissingSchedule error"),
+
+    // Whether the due run of the job at `index` should be skipped, because its guard fails
+    // or its idempotency key was already claimed
+    fn skips_run(&mut self, index: usize, now: SystemTime) -> bool {
+        let job = &mut self.jobs[index];
+        if job.guard.as_ref().is_some_and(|guard| !guard()) {
+            return true;
         }
-    }
-    
-    #[test]
-    fn test_add_job_no_handler() {
-        let mut scheduler = Scheduler::new();
-        // Create a job without a handler
-        let job = JobBuilder::new("no-handler")
-            .once(ScheduleTime::At(SystemTime::now() + Duration::from_secs(60)))
-            .build();
-            
-        let result = scheduler.add_job(job);
-        assert!(result.is_err());
-        match result {
-            Err(JobSchedulerError::HandlerNotBuilt) => {},
-            _ => panic!("Expected HandlerNotBuilt error"),
+        // Retries belong to the run that already claimed the key
+        if job.failed_attempts > 0 {
+            return false;
         }
+        let (Some(key_for), Some(store)) = (&job.idempotency_key, self.dedup_store.as_mut()) else {
+            return false;
+        };
+        let key = key_for(job.next_run.unwrap_or(now));
+        // A run that failed and stayed due fires again under the key it claimed
+        if job.claimed_key.as_ref() == Some(&key) {
+            return false;
+        }
+        if !store.claim(&key) {
+            return true;
+        }
+        job.claimed_key = Some(key);
+        false
     }
-    
+
+    // Record the result of a run of the job at `index` that started at `started`, where an
+    // `Err` means the handler panicked. Returns whether a retry was scheduled instead.
+    fn settle_run(
+        &mut self,
+        index: usize,
+        started: SystemTime,
+        now: SystemTime,
+        result: thread::Result<Result<(), JobSchedulerError>>,
+    ) -> Result<bool, JobSchedulerError> {
+        let job = &mut self.jobs[index];
+        let (result, circuit_open) = match result {
+            Ok(result) => {
+                job.consecutive_panics = 0;
+                (result, None)
+            }
+            Err(_) => {
+                let err = JobSchedulerError::ExecutionFailed(format!("handler for job {} panicked", job.id));
+                (Err(err), Self::count_panic(job, self.panic_threshold))
+            }
+        };
+        if circuit_open.is_none() && Self::schedule_retry(job, self.history_limit, started, now, &result) {
+            return Ok(true);
+        }
+        Self::record_outcome(job, self.error_handler.as_ref(), self.history_limit, started, result)?;
+        if let Some(err) = circuit_open {
+            Self::report_error(job, self.error_handler.as_ref(), err)?;
+        }
+        Ok(false)
+    }
+
+    // Advance the schedules of the job at `index` past the run that fired at `now`
+    fn finish_run(&mut self, index: usize, now: SystemTime, sweep_started: Instant, skipped: bool) {
+        let resolution = self.time_resolution;
+        let job = &mut self.jobs[index];
+        // Measured from the start of the sweep, as `now` is fixed for all of it
+        let finished = now + sweep_started.elapsed();
+        // update each schedule that fired
+        let offset = job.offset;
+        let window = job.active_window;
+        let mut coalesced = 0;
+        for sched in job.schedules.iter_mut() {
+            if schedule_due(sched, offset, now, resolution) {
+                if !skipped {
+                    sched.run_count += 1;
+                }
+                // Fixed-delay schedules count the next interval from completion
+                if let ScheduleType::Recurring(recurring) = &mut sched.schedule_type
+                    && recurring.fixed_delay
+                {
+                    recurring.next_run = finished;
+                }
+                sched.advance_at(now);
+                // This run stands in for every occurrence up to and including `now`
+                if job.coalesce
+                    && let Some(after_now) = now.checked_add(Duration::from_nanos(1))
+                {
+                    coalesced += Self::skip_runs_before(sched, after_now);
+                }
+                if let Some(window) = window {
+                    window.constrain(sched);
+                }
+            }
+        }
+        if coalesced > 0 {
+            log::debug!("Job {} coalesced {} missed run(s)", job.id, coalesced);
+        }
+        // recompute earliest next_run across schedules
+        job.refresh_next_run();
+        // A dedicated-thread job completes once its last run finishes
+        if !job.dedicated_thread || skipped {
+            Self::notify_if_complete(job);
+        }
+        if job.last_run == Some(now) {
+            let id = job.id;
+            self.arm_followers(id, now);
+        }
+    }
+
+    // Call the job's completion handler if it has no runs left
+    fn notify_if_complete(job: &Job) {
+        if job.state() == JobState::Completed
+            && let Some(on_complete) = &job.complete_handler
+        {
+            on_complete();
+        }
+    }
+
+    // Schedule the runs of jobs set to run a delay after the job with the given id, which
+    // last ran at `last_run`
+    fn arm_followers(&mut self, id: Uuid, last_run: SystemTime) {
+        for job in self.jobs.iter_mut() {
+            let mut armed = false;
+            for sched in job.schedules.iter_mut() {
+                if let ScheduleType::AfterJob(after) = &sched.schedule_type
+                    && after.job_id == id
+                {
+                    sched.next_run = last_run.checked_add(after.delay);
+                    if let Some(window) = job.active_window {
+                        window.constrain(sched);
+                    }
+                    armed = true;
+                }
+            }
+            if armed {
+                job.refresh_next_run();
+            }
+        }
+    }
+
+    // Record the outcome of a job's run that started at `started`
+    fn record_outcome(
+        job: &mut Job,
+        error_handler: Option<&ErrorHandler>,
+        history_limit: usize,
+        started: SystemTime,
+        result: Result<(), JobSchedulerError>,
+    ) -> Result<(), JobSchedulerError> {
+        let outcome = match &result {
+            Ok(()) => RunOutcome::Succeeded,
+            Err(err) => RunOutcome::Failed(err.to_string()),
+        };
+        job.record_run(RunRecord { started, outcome }, history_limit);
+        match result {
+            Ok(()) => job.last_run = Some(started),
+            Err(err) => Self::report_error(job, error_handler, err)?,
+        }
+        Ok(())
+    }
+
+    // Pass a job's error to its handlers. Per-job handler wins, then the global one,
+    // otherwise the error is propagated.
+    fn report_error(
+        job: &Job,
+        error_handler: Option<&ErrorHandler>,
+        err: JobSchedulerError,
+    ) -> Result<(), JobSchedulerError> {
+        if let Some(on_error) = &job.error_handler {
+            on_error(&err);
+        } else if let Some(on_any_error) = error_handler {
+            on_any_error(job.id, &err);
+        } else {
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    // Count a panicked run, pausing the job once it has panicked `threshold` times in a row.
+    // Returns the error to report if the job was paused.
+    fn count_panic(job: &mut Job, threshold: Option<u32>) -> Option<JobSchedulerError> {
+        job.consecutive_panics += 1;
+        let threshold = threshold?;
+        if job.consecutive_panics < threshold {
+            return None;
+        }
+        job.paused = true;
+        log::warn!("Job {} paused after {} consecutive panics", job.id, job.consecutive_panics);
+        Some(JobSchedulerError::ExecutionFailed(format!(
+            "circuit open: job {} paused after {} consecutive panics",
+            job.id, job.consecutive_panics
+        )))
+    }
+
+    // Retry a failed run later if the job's retry policy allows another attempt, recording
+    // the failure without reporting it. Returns whether a retry was scheduled.
+    fn schedule_retry(
+        job: &mut Job,
+        history_limit: usize,
+        started: SystemTime,
+        now: SystemTime,
+        result: &Result<(), JobSchedulerError>,
+    ) -> bool {
+        let Err(err) = result else {
+            job.failed_attempts = 0;
+            return false;
+        };
+        let Some(policy) = job.retry else {
+            return false;
+        };
+        job.failed_attempts += 1;
+        if job.failed_attempts >= policy.max_attempts {
+            job.failed_attempts = 0;
+            return false;
+        }
+        job.record_run(RunRecord { started, outcome: RunOutcome::Failed(err.to_string()) }, history_limit);
+        let delay = policy.strategy.delay(job.failed_attempts);
+        log::warn!("Job {} failed (attempt {} of {}), retrying in {:?}: {}", job.id, job.failed_attempts, policy.max_attempts, delay, err);
+        job.next_run = Some(now.checked_add(delay).unwrap_or(now));
+        true
+    }
+
+    // Start a job's handler on a thread named after the job. The handler and context move to
+    // the thread and are handed back through `completions` when the run finishes.
+    fn spawn_dedicated(
+        job: &mut Job,
+        started: SystemTime,
+        completions: &Sender<Completion>,
+        in_flight: &mut Vec<(Uuid, JoinHandle<()>)>,
+    ) -> Result<(), JobSchedulerError> {
+        let meta = job.meta(started);
+        let handler = job.handler.take().ok_or(JobSchedulerError::HandlerNotBuilt)?;
+        // Shared with the worker so the handler can be taken back if the thread fails to start
+        let taken = Arc::new(Mutex::new(Some((handler, job.context.take()))));
+        let id = job.id;
+        // Thread names cannot contain NUL bytes
+        let name = job.name.clone()
+            .filter(|name| !name.contains('\0'))
+            .unwrap_or_else(|| id.to_string());
+        let completions = completions.clone();
+        let worker_taken = Arc::clone(&taken);
+        let spawned = thread::Builder::new().name(name).spawn(move || {
+            let Some((handler, context)) = worker_taken.lock().ok().and_then(|mut taken| taken.take()) else {
+                return;
+            };
+            // Catch a panic so the handler still goes back to the job
+            let result = panic::catch_unwind(AssertUnwindSafe(|| handler(&meta, context.as_deref())));
+            // Nobody is left to record the outcome if the scheduler has been dropped
+            let _ = completions.send(Completion { id, started, handler, context, result });
+        });
+        match spawned {
+            Ok(worker) => {
+                in_flight.push((id, worker));
+                Ok(())
+            }
+            Err(err) => {
+                if let Some((handler, context)) = taken.lock().ok().and_then(|mut taken| taken.take()) {
+                    job.handler = Some(handler);
+                    job.context = context;
+                }
+                Err(err.into())
+            }
+        }
+    }
+
+    // Record the outcomes of finished dedicated-thread runs and return their handlers
+    fn collect_completions(&mut self) -> Result<(), JobSchedulerError> {
+        let now = SystemTime::now();
+        let mut errors = Vec::new();
+        while let Ok(done) = self.completion_receiver.try_recv() {
+            // The worker is finishing; its handle is no longer needed
+            self.in_flight.retain(|(id, _)| *id != done.id);
+            let Some(index) = self.jobs.iter().position(|job| job.id == done.id) else {
+                continue;
+            };
+            let job = &mut self.jobs[index];
+            job.handler = Some(done.handler);
+            job.context = done.context;
+            let succeeded = matches!(done.result, Ok(Ok(())));
+            match self.settle_run(index, done.started, now, done.result) {
+                Ok(true) => continue,
+                Ok(false) => {}
+                // Keep recording the other outcomes before reporting it
+                Err(err) => errors.push(err),
+            }
+            Self::notify_if_complete(&self.jobs[index]);
+            if succeeded {
+                self.arm_followers(done.id, done.started);
+            }
+        }
+        combine_errors(errors)
+    }
+
+    // A job may run once every dependency has succeeded since the job's own last run
+    fn dependencies_met(&self, job: &Job) -> bool {
+        job.dependencies.iter().all(|dependency_id| {
+            self.jobs.iter()
+                .find(|other| other.id == *dependency_id)
+                .and_then(|dependency| dependency.last_run)
+                .is_some_and(|dependency_run| job.last_run.is_none_or(|own_run| dependency_run > own_run))
+        })
+    }
+
+    /// Return the next scheduled run time among all jobs.
+    pub fn next_run(&self) -> Option<SystemTime> {
+        self.jobs.iter()
+            .filter(|job| !job.paused && !job.unbound)
+            .filter_map(|job| job.next_run)
+            .min()
+    }
+
+    // When the run loop should next sweep. Overdue jobs that cannot start yet, because a
+    // dependency has not run or their previous run is still going, are checked again after
+    // the max poll interval rather than on every pass.
+    fn next_wake(&self, now: SystemTime) -> Option<SystemTime> {
+        let retry = now.checked_add(self.max_poll_interval).unwrap_or(now);
+        self.jobs.iter()
+            .filter(|job| !job.paused && !job.unbound)
+            .filter_map(|job| {
+                let next = job.next_run?;
+                let held_back = !self.dependencies_met(job) || self.in_flight.iter().any(|(id, _)| *id == job.id);
+                Some(if held_back && next <= now { retry } else { next })
+            })
+            .min()
+    }
+
+    /// Return how long until the next scheduled run, formatted for people, e.g. "5m 3s".
+    ///
+    /// Rounded down to whole seconds, and "0s" if a run is overdue. Returns `None` when no
+    /// job has an upcoming run.
+    pub fn next_run_human(&self) -> Option<String> {
+        self.next_run_human_at(SystemTime::now())
+    }
+
+    // `next_run_human` measured from `now`
+    fn next_run_human_at(&self, now: SystemTime) -> Option<String> {
+        let wait = self.next_run()?.duration_since(now).unwrap_or(Duration::ZERO);
+        Some(format_sleep(wait))
+    }
+
+    /// Count the jobs using each kind of schedule, keyed by `ScheduleType::kind`.
+    ///
+    /// A job mixing schedule kinds is counted once under each kind it uses.
+    pub fn count_by_type(&self) -> HashMap<&'static str, usize> {
+        let mut counts = HashMap::new();
+        for job in &self.jobs {
+            let mut kinds: Vec<&'static str> = job.schedules.iter().map(|sched| sched.schedule_type.kind()).collect();
+            kinds.sort_unstable();
+            kinds.dedup();
+            for kind in kinds {
+                *counts.entry(kind).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// Return every projected run within `window` from now, across all jobs, sorted by time.
+    ///
+    /// Each entry pairs a job id with one of its upcoming fire times; overdue runs are
+    /// included. This is a projection only and does not change any job's state.
+    pub fn timeline(&self, window: Duration) -> Vec<(Uuid, SystemTime)> {
+        let now = SystemTime::now();
+        let end = now.checked_add(window);
+        let mut runs: Vec<(Uuid, SystemTime)> = self.jobs.iter()
+            .flat_map(|job| {
+                Self::projected_runs(job, now).into_iter().flat_map(move |runs| {
+                    runs.take_while(move |time| end.is_none_or(|end| *time <= end))
+                        .map(move |time| (job.id, time))
+                })
+            })
+            .collect();
+        runs.sort_by_key(|(id, time)| (*time, *id));
+        // Two schedules of one job firing together still produce a single run
+        runs.dedup();
+        runs
+    }
+
+    /// Return the `n` soonest projected runs across all jobs, in time order.
+    ///
+    /// Performs a k-way merge over every schedule's upcoming runs using a binary heap, so only
+    /// as many occurrences are projected as are needed. Does not change any job's state.
+    pub fn next_runs(&self, n: usize) -> Vec<(Uuid, SystemTime)> {
+        let now = SystemTime::now();
+        let mut sources: Vec<(Uuid, Box<dyn Iterator<Item = SystemTime> + '_>)> = self.jobs.iter()
+            .flat_map(|job| Self::projected_runs(job, now).into_iter().map(move |runs| (job.id, runs)))
+            .collect();
+        let mut heap = BinaryHeap::new();
+        for (index, (_, runs)) in sources.iter_mut().enumerate() {
+            if let Some(time) = runs.next() {
+                heap.push(Reverse((time, index)));
+            }
+        }
+
+        let mut result: Vec<(Uuid, SystemTime)> = Vec::with_capacity(n);
+        while result.len() < n {
+            let Some(Reverse((time, index))) = heap.pop() else {
+                break;
+            };
+            let (id, runs) = &mut sources[index];
+            // Two schedules of one job firing together still produce a single run; output is
+            // time-ordered so only the trailing entries at this time need checking
+            let duplicate = result.iter().rev()
+                .take_while(|(_, existing)| *existing == time)
+                .any(|(existing_id, _)| existing_id == id);
+            if !duplicate {
+                result.push((*id, time));
+            }
+            if let Some(next) = runs.next() {
+                heap.push(Reverse((next, index)));
+            }
+        }
+        result
+    }
+
+    // Upcoming runs of each of a job's schedules, with the job's offset applied
+    fn projected_runs(job: &Job, from: SystemTime) -> Vec<Box<dyn Iterator<Item = SystemTime> + '_>> {
+        let offset = job.offset;
+        job.schedules.iter()
+            .map(|sched| -> Box<dyn Iterator<Item = SystemTime> + '_> {
+                Box::new(Self::upcoming_runs(sched, from).map(move |time| offset.map_or(time, |offset| offset.apply(time))))
+            })
+            .collect()
+    }
+
+    /// Return a list of all jobs sorted by next run time (earliest first).
+    pub fn list_all_jobs(&self) -> Vec<&Job> {
+        self.list_jobs_sorted_by(SortKey::NextRun)
+    }
+
+    /// Return a list of all jobs sorted by `key`.
+    ///
+    /// Jobs that compare equal keep the order they were added in.
+    pub fn list_jobs_sorted_by(&self, key: SortKey) -> Vec<&Job> {
+        let mut job_refs: Vec<&Job> = self.jobs.iter().collect();
+        match key {
+            SortKey::NextRun => job_refs.sort_by(|a, b| missing_last(a.next_run, b.next_run)),
+            SortKey::Name => job_refs.sort_by(|a, b| missing_last(a.name.as_ref(), b.name.as_ref())),
+            SortKey::LastRun => job_refs.sort_by(|a, b| missing_last(a.last_run, b.last_run)),
+            SortKey::RunCount => job_refs.sort_by_key(|job| job.total_run_count()),
+            SortKey::Priority => job_refs.sort_by_key(|job| Reverse(job.priority)),
+        }
+        job_refs
+    }
+
+    /// Return the jobs matching `predicate`, in the order they were added.
+    ///
+    /// A general-purpose companion to the specific lookups, for filtering on any combination
+    /// of a job's fields.
+    pub fn find_jobs<P>(&self, predicate: P) -> Vec<&Job>
+    where P: Fn(&Job) -> bool {
+        self.jobs.iter().filter(|job| predicate(job)).collect()
+    }
+
+    /// Iterate over the jobs whose next run falls within `window` from now, in the order
+    /// they were added.
+    ///
+    /// Jobs already overdue are not included. Unlike `timeline`, jobs are filtered lazily
+    /// without collecting them first.
+    pub fn jobs_in_window(&self, window: Duration) -> impl Iterator<Item = &Job> {
+        self.jobs_in_window_at(SystemTime::now(), window)
+    }
+
+    // `jobs_in_window` treating `now` as the current time
+    fn jobs_in_window_at(&self, now: SystemTime, window: Duration) -> impl Iterator<Item = &Job> {
+        let end = now.checked_add(window);
+        self.jobs.iter().filter(move |job| {
+            job.next_run.is_some_and(|next| next >= now && end.is_none_or(|end| next <= end))
+        })
+    }
+
+    /// Group job ids by state: active, paused or completed.
+    ///
+    /// Ids within each state are in the order jobs were added; states with no jobs are absent.
+    pub fn jobs_by_state(&self) -> HashMap<JobState, Vec<Uuid>> {
+        let mut by_state: HashMap<JobState, Vec<Uuid>> = HashMap::new();
+        for job in &self.jobs {
+            by_state.entry(job.state()).or_default().push(job.id);
+        }
+        by_state
+    }
+
+    /// Check that every job has a handler, a schedule, an upcoming run and only depends on
+    /// jobs in this scheduler.
+    ///
+    /// Meant as a pre-flight check before entering the run loop. Rather than stopping at the
+    /// first bad job, returns each failing job's id with its first problem, in the order
+    /// jobs were added. Jobs that are paused or waiting on another job count as upcoming.
+    /// Dependencies may be added after the jobs that depend on them, so unknown ones are only
+    /// caught here; a job depending on a job that is never added never runs.
+    pub fn validate_all(&self) -> Result<(), Vec<(Uuid, JobSchedulerError)>> {
+        let problems: Vec<_> = self.jobs.iter()
+            .filter_map(|job| {
+                let unknown = job.dependencies.iter()
+                    .find(|dependency_id| !self.jobs.iter().any(|other| other.id == **dependency_id));
+                let problem = match job.ensure_runnable() {
+                    Err(err) => err,
+                    Ok(()) if let Some(dependency_id) = unknown => {
+                        JobSchedulerError::InvalidSchedule(format!("depends on unknown job {}", dependency_id))
+                    }
+                    Ok(()) => match job.schedule_status() {
+                        ScheduleStatus::NoUpcomingOccurrence => {
+                            JobSchedulerError::InvalidSchedule("no upcoming occurrence".to_string())
+                        }
+                        ScheduleStatus::Completed => {
+                            JobSchedulerError::InvalidSchedule("every schedule has used up its runs".to_string())
+                        }
+                        _ => return None,
+                    },
+                };
+                Some((job.id, problem))
+            })
+            .collect();
+        if problems.is_empty() { Ok(()) } else { Err(problems) }
+    }
+
+    /// Return an owned copy of every job's metadata, in the order jobs were added.
+    pub fn snapshot(&self) -> Vec<JobSnapshot> {
+        self.jobs.iter().map(Job::snapshot).collect()
+    }
+
+    /// Fast-forward recurring schedules that fell more than one interval behind `now`.
+    ///
+    /// Intended for use after the host wakes from sleep: each stale schedule moves to its
+    /// first occurrence at or after `now` without firing the missed runs or counting them
+    /// towards `max_runs`. Returns the total number of skipped occurrences.
+    pub fn reschedule_missed(&mut self, now: SystemTime) -> u64 {
+        let mut total_skipped = 0;
+        for job in self.jobs.iter_mut() {
+            let mut job_skipped = 0;
+            for sched in job.schedules.iter_mut() {
+                // Only schedules more than one interval behind are stale
+                if let ScheduleType::Recurring(recurring) = &sched.schedule_type
+                    && now.duration_since(recurring.next_run).is_ok_and(|behind| behind > recurring.interval.delta())
+                {
+                    job_skipped += Self::skip_runs_before_within(sched, now, job.active_window);
+                }
+            }
+            if job_skipped > 0 {
+                log::info!("Job {} skipped {} missed interval(s)", job.id, job_skipped);
+                job.refresh_next_run();
+                total_skipped += job_skipped;
+            }
+        }
+        total_skipped
+    }
+
+    // Move a recurring schedule to its first occurrence at or after `cutoff`, returning how many
+    // occurrences were skipped
+    fn skip_runs_before(sched: &mut Schedule, cutoff: SystemTime) -> u64 {
+        if sched.peek_next_run().is_none() {
+            return 0;
+        }
+        let ScheduleType::Recurring(recurring) = &mut sched.schedule_type else {
+            return 0;
+        };
+        let delta = recurring.interval.delta();
+        let behind = match cutoff.duration_since(recurring.next_run) {
+            Ok(behind) => behind,
+            Err(_) => return 0,
+        };
+        if delta.is_zero() || behind.is_zero() {
+            return 0;
+        }
+        // Calendar years differ in length, so step through them one at a time
+        if let RecurringInterval::Yearly(_) = recurring.interval {
+            let mut skipped = 0;
+            while recurring.next_run < cutoff {
+                let Some(next) = recurring.following(recurring.next_run) else {
+                    break;
+                };
+                recurring.next_run = next;
+                skipped += 1;
+            }
+            sched.next_run = Some(recurring.next_run);
+            return skipped;
+        }
+        // Number of occurrences strictly before `cutoff`
+        let skipped = behind.as_nanos().div_ceil(delta.as_nanos());
+        let offset = delta.as_nanos() * skipped;
+        recurring.next_run += Duration::new(
+            (offset / 1_000_000_000) as u64,
+            (offset % 1_000_000_000) as u32,
+        );
+        sched.next_run = Some(recurring.next_run);
+        skipped as u64
+    }
+
+    // `skip_runs_before`, then moving a schedule that skipped runs back inside the job's
+    // active window, if it has one
+    fn skip_runs_before_within(sched: &mut Schedule, cutoff: SystemTime, window: Option<TimeWindow>) -> u64 {
+        let skipped = Self::skip_runs_before(sched, cutoff);
+        if skipped > 0
+            && let Some(window) = window
+        {
+            window.constrain(sched);
+        }
+        skipped
+    }
+
+    // Skip runs missed by more than the catch-up limit, e.g. after the clock jumps forward
+    fn limit_catchup(&mut self, now: SystemTime) {
+        let Some(cutoff) = self.max_catchup.and_then(|limit| now.checked_sub(limit)) else {
+            return;
+        };
+        for job in self.jobs.iter_mut() {
+            let window = job.active_window;
+            let skipped: u64 = job.schedules.iter_mut()
+                .map(|sched| Self::skip_runs_before_within(sched, cutoff, window))
+                .sum();
+            if skipped > 0 {
+                log::warn!("Job {} skipped {} run(s) missed beyond the catch-up limit", job.id, skipped);
+                job.refresh_next_run();
+            }
+        }
+    }
+
+    // Hold back opportunistic runs whose load check fails before their deadline, asking
+    // again after the max poll interval
+    fn defer_opportunistic(&mut self, now: SystemTime) {
+        let resolution = self.time_resolution;
+        for job in self.jobs.iter_mut().filter(|job| !job.paused) {
+            let mut deferred = false;
+            for sched in job.schedules.iter_mut() {
+                if let ScheduleType::Opportunistic(opportunistic) = &sched.schedule_type
+                    && sched.pending_run().is_some_and(|next| due_by(next, now, resolution))
+                    && !opportunistic.ready_at(now)
+                {
+                    let retry = now.checked_add(self.max_poll_interval).unwrap_or(opportunistic.deadline);
+                    sched.next_run = Some(retry.min(opportunistic.deadline));
+                    deferred = true;
+                }
+            }
+            if deferred {
+                job.refresh_next_run();
+            }
+        }
+    }
+
+    /// Continuously run pending jobs without busy-waiting, sleeping until the next job is due.
+    ///
+    /// Each sleep is capped at the configured max poll interval and ends early if a job is
+    /// added through a `SchedulerHandle`. Returns once no jobs have an upcoming run.
+    pub fn run_non_blocking(&mut self) -> Result<(), JobSchedulerError> {
+        self.receive_jobs();
+        while let Some(next) = self.next_wake(SystemTime::now()) {
+            let now = SystemTime::now();
+            if next > now {
+                let duration = next.duration_since(now).unwrap_or_else(|_| Duration::from_secs(0));
+                let sleep = duration.min(self.max_poll_interval);
+                log::trace!("Sleeping {} (next run in {})", format_sleep(sleep), format_sleep(duration));
+                if let Ok(job) = self.job_receiver.recv_timeout(sleep) {
+                    // A new job may be due before the one we were waiting for
+                    self.accept_sent_job(job);
+                    self.receive_jobs();
+                    continue;
+                }
+            }
+            self.run_pending()?;
+            self.receive_jobs();
+        }
+        // Wait for runs still on dedicated threads so their outcomes are recorded
+        while !self.in_flight.is_empty() {
+            thread::sleep(self.max_poll_interval.min(Duration::from_millis(10)));
+            self.collect_completions()?;
+        }
+        if let Some(on_idle) = &self.idle_handler {
+            on_idle();
+        }
+        Ok(())
+    }
+
+    // Projected fire times of a schedule from `from` onwards, limited by its remaining runs
+    fn upcoming_runs(schedule: &Schedule, from: SystemTime) -> Box<dyn Iterator<Item = SystemTime> + '_> {
+        let remaining = schedule.max_runs
+            .map_or(usize::MAX, |max| max.saturating_sub(schedule.run_count) as usize);
+        let Some(next) = schedule.pending_run() else {
+            return Box::new(std::iter::empty());
+        };
+        let runs: Box<dyn Iterator<Item = SystemTime>> = match &schedule.schedule_type {
+            // Later random picks are not known until the schedule advances
+            ScheduleType::Once(_)
+            | ScheduleType::Immediate
+            | ScheduleType::Opportunistic(_)
+            | ScheduleType::Random(_)
+            | ScheduleType::RandomWindow(_)
+            | ScheduleType::AfterJob(_) => {
+                Box::new(std::iter::once(next))
+            }
+            ScheduleType::Recurring(recurring) => {
+                if recurring.interval.delta().is_zero() {
+                    Box::new(std::iter::once(recurring.next_run))
+                } else {
+                    let recurring = recurring.clone();
+                    Box::new(std::iter::successors(Some(recurring.next_run), move |time| recurring.following(*time)))
+                }
+            }
+            ScheduleType::Cron { schedule: cron_schedule, .. } => {
+                let after = chrono::DateTime::<Utc>::from(from.max(next));
+                Box::new(std::iter::once(next).chain(cron_schedule.after_owned(after).map(SystemTime::from)))
+            }
+            ScheduleType::ZonedCron(zoned) => {
+                let later = std::iter::successors(zoned.next_after(from.max(next)), |time| zoned.next_after(*time));
+                Box::new(std::iter::once(next).chain(later))
+            }
+            ScheduleType::BusinessDayCron(business) => {
+                let later = std::iter::successors(business.next_after(from.max(next)), |time| business.next_after(*time));
+                Box::new(std::iter::once(next).chain(later))
+            }
+            ScheduleType::CronEveryNth(nth) => {
+                Box::new(std::iter::successors(Some(next), |time| nth.next_after(*time, *time)))
+            }
+            ScheduleType::Dynamic(dynamic) => {
+                let later = std::iter::successors(dynamic.next_after(from.max(next)), |time| dynamic.next_after(*time));
+                Box::new(std::iter::once(next).chain(later))
+            }
+            ScheduleType::AtTimes(daily) => {
+                let later = std::iter::successors(daily.next_after(from.max(next)), |time| daily.next_after(*time));
+                Box::new(std::iter::once(next).chain(later))
+            }
+        };
+        Box::new(runs.take(remaining))
+    }
+}
+
+// Return the errors from a sweep, combining several into one `ExecutionFailed`
+fn combine_errors(mut errors: Vec<JobSchedulerError>) -> Result<(), JobSchedulerError> {
+    if errors.len() > 1 {
+        let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        return Err(JobSchedulerError::ExecutionFailed(format!(
+            "{} jobs failed: {}",
+            errors.len(),
+            messages.join("; ")
+        )));
+    }
+    errors.pop().map_or(Ok(()), Err)
+}
+
+// Whether a schedule has a run due by `now`. Immediate runs are due on any sweep, even one
+// whose clock reads earlier than when they were scheduled.
+fn schedule_due(sched: &Schedule, offset: Option<RunOffset>, now: SystemTime, resolution: Option<Duration>) -> bool {
+    let Some(run) = sched.pending_run() else {
+        return false;
+    };
+    matches!(sched.schedule_type, ScheduleType::Immediate)
+        || due_by(offset.map_or(run, |offset| offset.apply(run)), now, resolution)
+}
+
+// Whether a run at `time` is due at `now`, rounding both down to `resolution` if set
+fn due_by(time: SystemTime, now: SystemTime, resolution: Option<Duration>) -> bool {
+    match resolution {
+        Some(resolution) => round_down(time, resolution) <= round_down(now, resolution),
+        None => time <= now,
+    }
+}
+
+// Round a time down to a multiple of `resolution` since the Unix epoch. Earlier times are
+// left as they are.
+fn round_down(time: SystemTime, resolution: Duration) -> SystemTime {
+    let Ok(since_epoch) = time.duration_since(SystemTime::UNIX_EPOCH) else {
+        return time;
+    };
+    let excess = since_epoch.as_nanos() % resolution.as_nanos();
+    // The excess is below the resolution, which is a `Duration`, so it fits
+    time - Duration::from_nanos(excess as u64)
+}
+
+// Compare optional values, ordering `None` after every value
+fn missing_last<T: Ord>(a: Option<T>, b: Option<T>) -> std::cmp::Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    }
+}
+
+// Human-readable duration, e.g. "1h 1m 1s", at whole-second precision
+// unless the sleep is shorter than a second
+fn format_sleep(duration: Duration) -> String {
+    let duration = if duration < Duration::from_secs(1) {
+        duration
+    } else {
+        Duration::from_secs(duration.as_secs())
+    };
+    humantime::format_duration(duration).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scheduler::types::RecurringSchedule;
+    use crate::job::{JobBuilder, JobStats};
+    use crate::utils::time::ScheduleTime;
+
+    use super::*;
+    use std::thread::sleep;
+
+
+    // Helper function for tests
+    fn dummy_handler() {
+        // Removed Ok(())
+    }
+    
     #[test]
-    fn test_next_run_single_job() -> Result<(), JobSchedulerError> {
+    fn test_new_scheduler_empty() {
+        let scheduler = Scheduler::new();
+        assert_eq!(scheduler.jobs.len(), 0);
+        assert_eq!(scheduler.next_run(), None);
+        assert_eq!(scheduler.list_all_jobs().len(), 0);
+    }
+    
+    #[test]
+    fn test_add_job() -> Result<(), JobSchedulerError> {
         let mut scheduler = Scheduler::new();
-        let target_time = SystemTime::now() + Duration::from_secs(60);
-        
         let job = JobBuilder::new("test-job")
-            .once(ScheduleTime::At(target_time))
+            .once(ScheduleTime::At(SystemTime::now() + Duration::from_secs(60)))
             .add_handler(dummy_handler)
             .build();
             
         scheduler.add_job(job)?;
+        assert_eq!(scheduler.jobs.len(), 1);
+        assert_eq!(scheduler.list_all_jobs().len(), 1);
         
-        let next = scheduler.next_run();
-        assert!(next.is_some());
-        
-        let diff = if target_time > next.unwrap() {
-            target_time.duration_since(next.unwrap())
-        } else {
-            next.unwrap().duration_since(target_time)
-        };
-        
-        assert!(diff.unwrap_or_default() < Duration::from_millis(10));
+        let job_ref = scheduler.list_all_jobs()[0];
+        assert_eq!(job_ref.name, Some("test-job".to_string()));
         
         Ok(())
     }
     
     #[test]
-    fn test_next_run_multiple_jobs() -> Result<(), JobSchedulerError> {
+    fn test_add_job_no_schedule() {
         let mut scheduler = Scheduler::new();
-        let time1 = SystemTime::now() + Duration::from_secs(60);
-        let time2 = SystemTime::now() + Duration::from_secs(30); // Earlier time
-        
-        let job1 = JobBuilder::new("job1")
-            .once(ScheduleTime::At(time1))
-            .add_handler(dummy_handler)
-            .build();
-            
-        let job2 = JobBuilder::new("job2")
-            .once(ScheduleTime::At(time2))
+        // Create a job without any schedules
+        let job = JobBuilder::new("no-schedule")
+            .add_handler(dummy_handler)
+            .build();
+            
+        let result = scheduler.add_job(job);
+        assert!(result.is_err());
+        match result {
+            Err(JobSchedulerError::MissingSchedule) => {},
+            _ => panic!("Expected MissingSchedule error"),
+        }
+    }
+    
+    #[test]
+    fn test_add_job_no_handler() {
+        let mut scheduler = Scheduler::new();
+        // Create a job without a handler
+        let job = JobBuilder::new("no-handler")
+            .once(ScheduleTime::At(SystemTime::now() + Duration::from_secs(60)))
+            .build();
+            
+        let result = scheduler.add_job(job);
+        assert!(result.is_err());
+        match result {
+            Err(JobSchedulerError::HandlerNotBuilt) => {},
+            _ => panic!("Expected HandlerNotBuilt error"),
+        }
+    }
+    
+    #[test]
+    fn test_next_run_single_job() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        let target_time = SystemTime::now() + Duration::from_secs(60);
+        
+        let job = JobBuilder::new("test-job")
+            .once(ScheduleTime::At(target_time))
+            .add_handler(dummy_handler)
+            .build();
+            
+        scheduler.add_job(job)?;
+        
+        let next = scheduler.next_run();
+        assert!(next.is_some());
+        
+        let diff = if target_time > next.unwrap() {
+            target_time.duration_since(next.unwrap())
+        } else {
+            next.unwrap().duration_since(target_time)
+        };
+        
+        assert!(diff.unwrap_or_default() < Duration::from_millis(10));
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_next_run_multiple_jobs() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        let time1 = SystemTime::now() + Duration::from_secs(60);
+        let time2 = SystemTime::now() + Duration::from_secs(30); // Earlier time
+        
+        let job1 = JobBuilder::new("job1")
+            .once(ScheduleTime::At(time1))
+            .add_handler(dummy_handler)
+            .build();
+            
+        let job2 = JobBuilder::new("job2")
+            .once(ScheduleTime::At(time2))
             .add_handler(dummy_handler)
             .build();
             
@@ -335,280 +1742,2224 @@ mod tests {
     }
     
     #[test]
-    fn test_run_pending_job() -> Result<(), JobSchedulerError> {
+    fn test_run_pending_job() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        
+        // Create a job that will run immediately
+        let job = JobBuilder::new("immediate")
+            .once(ScheduleTime::At(SystemTime::now()))
+            .add_handler(dummy_handler)
+            .build();
+            
+        scheduler.add_job(job)?;
+        
+        // Should be one job before running
+        assert_eq!(scheduler.jobs.len(), 1);
+        
+        // Run pending jobs
+        scheduler.run_pending()?;
+        
+        // Should still have one job but with updated last_run time and null next_run
+        assert_eq!(scheduler.jobs.len(), 1);
+        assert!(scheduler.jobs[0].last_run.is_some());
+        assert!(scheduler.jobs[0].next_run.is_none()); // Should be None after running once
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_run_recurring_jobs() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        
+        // Create a job that recurs every second
+        let recur_time = SystemTime::now();
+        
+        let job = JobBuilder::new("recurring")
+            .recurring(RecurringInterval::Secondly(1), Some(ScheduleTime::At(recur_time)))
+            .add_handler(dummy_handler)
+            .build();
+            
+        scheduler.add_job(job)?;
+        
+        // Initial state check
+        assert_eq!(scheduler.jobs.len(), 1);
+        assert!(scheduler.jobs[0].next_run.is_some());
+        
+        // Run pending jobs
+        scheduler.run_pending()?;
+        
+        // Should have updated last_run and scheduled next run
+        let last_run = scheduler.jobs[0].last_run;
+        assert!(last_run.is_some());
+        
+        let next_run = scheduler.jobs[0].next_run;
+        assert!(next_run.is_some());
+        
+        // Next run should be approximately one second after the initial time
+        let expected_next = recur_time + Duration::from_secs(1);
+        let diff = if expected_next > next_run.unwrap() {
+            expected_next.duration_since(next_run.unwrap())
+        } else {
+            next_run.unwrap().duration_since(expected_next)
+        };
+        
+        assert!(diff.unwrap_or_default() < Duration::from_millis(100));
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_run_job_with_max_runs() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        
+        // Create a recurring job with max 2 runs
+        let recur_time = SystemTime::now();
+        
+        let job = JobBuilder::new("limited-runs")
+            .recurring(RecurringInterval::Secondly(1), Some(ScheduleTime::At(recur_time)))
+            .max_repeat(2)
+            .add_handler(dummy_handler)
+            .build();
+            
+        scheduler.add_job(job)?;
+        
+        // Run first execution
+        scheduler.run_pending_at(recur_time)?;
+        assert!(scheduler.jobs[0].next_run.is_some());
+        
+        // Run second execution once the next schedule is ready
+        scheduler.run_pending_at(recur_time + Duration::from_secs(1))?;
+        
+        // Run again, but there should be no next run since we hit max_runs=2
+        scheduler.run_pending_at(recur_time + Duration::from_secs(2))?;
+        assert!(scheduler.jobs[0].next_run.is_none());
+        assert_eq!(scheduler.jobs[0].total_run_count(), 2);
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_burst_fires_once_per_sweep_up_to_count() -> Result<(), JobSchedulerError> {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let mut scheduler = Scheduler::new();
+        let counter = Arc::new(AtomicU32::new(0));
+        let counter_clone = Arc::clone(&counter);
+        let job = JobBuilder::new("burst")
+            .burst(3)
+            .add_handler(move || {
+                counter_clone.fetch_add(1, Ordering::SeqCst);
+            })
+            .build();
+        scheduler.add_job(job)?;
+
+        for expected in 1..=3 {
+            scheduler.run_pending()?;
+            assert_eq!(counter.load(Ordering::SeqCst), expected);
+        }
+        assert!(scheduler.next_run().is_none());
+
+        scheduler.run_pending()?;
+        assert_eq!(counter.load(Ordering::SeqCst), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reschedule_missed() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        let now = SystemTime::now();
+        let start = now - Duration::from_secs(95);
+        let stale = JobBuilder::new("stale")
+            .recurring(RecurringInterval::Secondly(10), Some(ScheduleTime::At(start)))
+            .add_handler(dummy_handler)
+            .build();
+        // Less than one interval behind, so it is left alone
+        let recent = JobBuilder::new("recent")
+            .recurring(RecurringInterval::Secondly(10), Some(ScheduleTime::At(now - Duration::from_secs(5))))
+            .add_handler(dummy_handler)
+            .build();
+        scheduler.add_job(stale)?;
+        scheduler.add_job(recent)?;
+
+        // Occurrences at -95s, -85s, ..., -5s are skipped
+        assert_eq!(scheduler.reschedule_missed(now), 10);
+        assert_eq!(scheduler.jobs[0].next_run, Some(start + Duration::from_secs(100)));
+        assert_eq!(scheduler.jobs[0].schedules[0].run_count, 0);
+        assert_eq!(scheduler.jobs[1].next_run, Some(now - Duration::from_secs(5)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_dependent_job_waits_for_dependency() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        let job_a = JobBuilder::new("a")
+            .once(ScheduleTime::At(SystemTime::now() + Duration::from_millis(200)))
+            .add_handler(dummy_handler)
+            .build();
+        let job_a_id = job_a.id;
+        // B is due immediately but must wait for A
+        let job_b = JobBuilder::new("b")
+            .once(ScheduleTime::At(SystemTime::now()))
+            .after(job_a_id)
+            .add_handler(dummy_handler)
+            .build();
+        scheduler.add_job(job_b)?;
+        scheduler.add_job(job_a)?;
+
+        scheduler.run_pending()?;
+        assert!(scheduler.jobs[0].last_run.is_none());
+        assert!(scheduler.jobs[0].next_run.is_some());
+
+        let later = SystemTime::now() + Duration::from_millis(250);
+        scheduler.run_pending_at(later)?;
+        assert!(scheduler.jobs[1].last_run.is_some());
+        // B was checked before A ran in this sweep, so it fires on the next one
+        scheduler.run_pending_at(later)?;
+        assert!(scheduler.jobs[0].last_run.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_handler_precedence() -> Result<(), JobSchedulerError> {
+        use std::sync::{Arc, Mutex};
+
+        // A handler that needs a context but has none always fails
+        fn failing_job(name: &str) -> JobBuilder {
+            JobBuilder::new(name)
+                .once(ScheduleTime::At(SystemTime::now()))
+                .add_handler_with_ctx(|_: &u32| {})
+        }
+
+        // Without any handler the error propagates
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(failing_job("unhandled").build())?;
+        assert!(matches!(scheduler.run_pending(), Err(JobSchedulerError::ExecutionFailed(_))));
+
+        let global_calls = Arc::new(Mutex::new(Vec::new()));
+        let own_calls = Arc::new(Mutex::new(0));
+        let mut scheduler = Scheduler::new();
+        let global_calls_clone = Arc::clone(&global_calls);
+        scheduler.on_any_error(move |id, _| global_calls_clone.lock().unwrap().push(id));
+
+        let own_calls_clone = Arc::clone(&own_calls);
+        let with_own = failing_job("with-own")
+            .on_error(move |_| *own_calls_clone.lock().unwrap() += 1)
+            .build();
+        let without_own = failing_job("without-own").build();
+        let without_own_id = without_own.id;
+        scheduler.add_job(with_own)?;
+        scheduler.add_job(without_own)?;
+
+        scheduler.run_pending()?;
+        assert_eq!(*own_calls.lock().unwrap(), 1);
+        assert_eq!(*global_calls.lock().unwrap(), vec![without_own_id]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixed_delay_vs_fixed_rate() -> Result<(), JobSchedulerError> {
+        let slow_handler = || sleep(Duration::from_millis(300));
+        let start = SystemTime::now();
+        let mut scheduler = Scheduler::new();
+        let fixed_rate = JobBuilder::new("fixed-rate")
+            .recurring(RecurringInterval::Secondly(1), Some(ScheduleTime::At(start)))
+            .add_handler(slow_handler)
+            .build();
+        let fixed_delay = JobBuilder::new("fixed-delay")
+            .fixed_delay(RecurringInterval::Secondly(1), Some(ScheduleTime::At(start)))
+            .add_handler(slow_handler)
+            .build();
+        scheduler.add_job(fixed_rate)?;
+        scheduler.add_job(fixed_delay)?;
+
+        scheduler.run_pending()?;
+
+        // Fixed rate advances from the scheduled time regardless of how long the run took
+        assert_eq!(scheduler.jobs[0].next_run, Some(start + Duration::from_secs(1)));
+        // Fixed delay waits a full interval after the (slow) run completed
+        let delayed_next = scheduler.jobs[1].next_run.unwrap();
+        assert!(delayed_next >= start + Duration::from_millis(1600));
+        Ok(())
+    }
+
+    #[test]
+    fn test_timeline() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        let now = SystemTime::now();
+        let recurring = JobBuilder::new("recurring")
+            .recurring(RecurringInterval::Secondly(20), Some(ScheduleTime::At(now + Duration::from_secs(10))))
+            .add_handler(dummy_handler)
+            .build();
+        let recurring_id = recurring.id;
+        let once = JobBuilder::new("once")
+            .once(ScheduleTime::At(now + Duration::from_secs(15)))
+            .add_handler(dummy_handler)
+            .build();
+        let once_id = once.id;
+        let far = JobBuilder::new("far")
+            .once(ScheduleTime::At(now + Duration::from_secs(3600)))
+            .add_handler(dummy_handler)
+            .build();
+        scheduler.add_job(recurring)?;
+        scheduler.add_job(once)?;
+        scheduler.add_job(far)?;
+
+        let timeline = scheduler.timeline(Duration::from_secs(60));
+        assert_eq!(timeline, vec![
+            (recurring_id, now + Duration::from_secs(10)),
+            (once_id, now + Duration::from_secs(15)),
+            (recurring_id, now + Duration::from_secs(30)),
+            (recurring_id, now + Duration::from_secs(50)),
+        ]);
+        // Projection does not mutate the jobs
+        assert_eq!(scheduler.jobs[0].next_run, Some(now + Duration::from_secs(10)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_next_runs() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        let now = SystemTime::now();
+        let fast = JobBuilder::new("fast")
+            .recurring(RecurringInterval::Secondly(10), Some(ScheduleTime::At(now + Duration::from_secs(10))))
+            .add_handler(dummy_handler)
+            .build();
+        let fast_id = fast.id;
+        let slow = JobBuilder::new("slow")
+            .recurring(RecurringInterval::Secondly(25), Some(ScheduleTime::At(now + Duration::from_secs(5))))
+            .add_handler(dummy_handler)
+            .build();
+        let slow_id = slow.id;
+        scheduler.add_job(fast)?;
+        scheduler.add_job(slow)?;
+
+        // Ties are broken by the order jobs were added
+        assert_eq!(scheduler.next_runs(5), vec![
+            (slow_id, now + Duration::from_secs(5)),
+            (fast_id, now + Duration::from_secs(10)),
+            (fast_id, now + Duration::from_secs(20)),
+            (fast_id, now + Duration::from_secs(30)),
+            (slow_id, now + Duration::from_secs(30)),
+        ]);
+
+        // Asking for more than exist returns everything available
+        let mut once_only = Scheduler::new();
+        once_only.add_job(JobBuilder::new("once").once(ScheduleTime::At(now)).add_handler(dummy_handler).build())?;
+        assert_eq!(once_only.next_runs(10).len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_once_schedule_kept_alongside_recurring() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        let now = SystemTime::now();
+        let job = JobBuilder::new("mixed")
+            .recurring(RecurringInterval::Secondly(60), Some(ScheduleTime::At(now)))
+            .once(ScheduleTime::At(now + Duration::from_secs(30)))
+            .add_handler(dummy_handler)
+            .build();
+        scheduler.add_job(job)?;
+
+        scheduler.run_pending()?;
+        // The recurring run fired; the pending once run must not be forgotten
+        assert_eq!(scheduler.jobs[0].next_run, Some(now + Duration::from_secs(30)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_shutdown_waits_for_in_flight_jobs() -> Result<(), JobSchedulerError> {
+        fn sleeping_job(name: &str, millis: u64) -> Job {
+            JobBuilder::new(name)
+                .now_once()
+                .dedicated_thread()
+                .add_handler(move || sleep(Duration::from_millis(millis)))
+                .build()
+        }
+
+        let scheduler = Scheduler::new();
+        assert!(scheduler.shutdown(Duration::from_millis(10)).is_ok());
+
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(sleeping_job("quick", 50))?;
+        scheduler.run_pending()?;
+        assert_eq!(scheduler.in_flight.len(), 1);
+        assert!(scheduler.shutdown(Duration::from_secs(1)).is_ok());
+
+        let mut scheduler = Scheduler::new();
+        let stuck = sleeping_job("stuck", 500);
+        let stuck_id = stuck.id;
+        scheduler.add_job(stuck)?;
+        scheduler.run_pending()?;
+        match scheduler.shutdown(Duration::from_millis(50)) {
+            Err(JobSchedulerError::ExecutionFailed(msg)) => assert!(msg.contains(&stuck_id.to_string())),
+            _ => panic!("Expected ExecutionFailed error"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_offset_before_cron() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        // Every minute on the minute, shifted to run 10 seconds early
+        let job = JobBuilder::new("early-cron")
+            .cron("0 * * * * * *")
+            .offset(Duration::from_secs(10), true)
+            .add_handler(dummy_handler)
+            .build();
+        let base = job.schedules[0].next_run.unwrap();
+        assert_eq!(job.next_run, Some(base - Duration::from_secs(10)));
+        scheduler.add_job(job)?;
+
+        // Pretend the shifted time has arrived by moving the base occurrence close to now
+        let base = SystemTime::now() + Duration::from_secs(5);
+        scheduler.jobs[0].schedules[0].next_run = Some(base);
+        scheduler.jobs[0].refresh_next_run();
+        scheduler.run_pending()?;
+        assert!(scheduler.jobs[0].last_run.is_some());
+        assert_eq!(scheduler.jobs[0].schedules[0].run_count, 1);
+
+        // The early run consumed this occurrence, so the schedule moves past it
+        let next_base = scheduler.jobs[0].schedules[0].next_run.unwrap();
+        assert!(next_base > base);
+        assert_eq!(scheduler.jobs[0].next_run, Some(next_base - Duration::from_secs(10)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_offset_after_recurring() -> Result<(), JobSchedulerError> {
+        let start = SystemTime::now() + Duration::from_secs(60);
+        let job = JobBuilder::new("late-recurring")
+            .offset(Duration::from_secs(5), false)
+            .recurring(RecurringInterval::Minutely(1), Some(ScheduleTime::At(start)))
+            .add_handler(dummy_handler)
+            .build();
+        assert_eq!(job.next_run, Some(start + Duration::from_secs(5)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cron_max_runs_counts_fires() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        let job = JobBuilder::new("cron-twice")
+            .cron("* * * * * * *")
+            .max_repeat(2)
+            .add_handler(dummy_handler)
+            .build();
+        scheduler.add_job(job)?;
+        let start = SystemTime::now();
+        for step in 1..=2 {
+            scheduler.run_pending_at(start + Duration::from_millis(1100) * step)?;
+        }
+        assert_eq!(scheduler.jobs[0].schedules[0].run_count, 2);
+        assert!(scheduler.jobs[0].next_run.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_random_window_advances_each_cycle() {
+        use chrono::{NaiveTime, Timelike};
+
+        let window_start = NaiveTime::from_hms_opt(1, 0, 0).unwrap();
+        let window_end = NaiveTime::from_hms_opt(4, 0, 0).unwrap();
+        let mut job = JobBuilder::new("nightly")
+            .recurring_random_window(RecurringInterval::Daily(1), window_start, window_end)
+            .add_handler(dummy_handler)
+            .build();
+
+        let in_window = |time: SystemTime| {
+            let time = chrono::DateTime::<Utc>::from(time).time();
+            time >= window_start && time < window_end
+        };
+        let first = job.next_run.unwrap();
+        assert!(in_window(first));
+
+        let sched = &mut job.schedules[0];
+        let second = sched.advance_at(SystemTime::now()).unwrap();
+        assert!(in_window(second));
+        let first_day = chrono::DateTime::<Utc>::from(first).date_naive();
+        let second_day = chrono::DateTime::<Utc>::from(second).date_naive();
+        assert_eq!(second_day, first_day.succ_opt().unwrap());
+        assert!(chrono::DateTime::<Utc>::from(second).hour() < 4);
+    }
+
+    #[test]
+    fn test_list_all_jobs() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        
+        let job1 = JobBuilder::new("job1")
+            .once(ScheduleTime::At(SystemTime::now() + Duration::from_secs(60)))
+            .add_handler(dummy_handler)
+            .build();
+            
+        let job2 = JobBuilder::new("job2")
+            .once(ScheduleTime::At(SystemTime::now() + Duration::from_secs(30)))
+            .add_handler(dummy_handler)
+            .build();
+            
+        let job3 = JobBuilder::new("job3")
+            .once(ScheduleTime::At(SystemTime::now() + Duration::from_secs(90)))
+            .add_handler(dummy_handler)
+            .build();
+            
+        scheduler.add_job(job1)?;
+        scheduler.add_job(job2)?;
+        scheduler.add_job(job3)?;
+        
+        let all_jobs = scheduler.list_all_jobs();
+        assert_eq!(all_jobs.len(), 3);
+        // Jobs sorted by next_run: time2 (30s), time1 (60s), time3 (90s)
+        assert_eq!(all_jobs[0].name, Some("job2".to_string()));
+        assert_eq!(all_jobs[1].name, Some("job1".to_string()));
+        assert_eq!(all_jobs[2].name, Some("job3".to_string()));
+
+        Ok(())
+    }
+    
+    #[test]
+    fn test_compute_next_run_recurring_intervals() {
+        // Test different recurring interval calculations
+        let now = SystemTime::now();
+        
+        // Test secondly
+        let mut secondly_sched = Schedule {
+            schedule_type: ScheduleType::Recurring(RecurringSchedule::new(RecurringInterval::Secondly(5), now)),
+            max_runs: None,
+            run_count: 0,
+            next_run: Some(now),
+        };
+        let next_secondly = secondly_sched.advance_at(SystemTime::now()).unwrap();
+        assert_eq!(next_secondly, now + Duration::from_secs(5));
+        
+        // Test hourly
+        let mut hourly_sched = Schedule {
+            schedule_type: ScheduleType::Recurring(RecurringSchedule::new(RecurringInterval::Hourly(2), now)),
+            max_runs: None,
+            run_count: 0,
+            next_run: Some(now),
+        };
+        let next_hourly = hourly_sched.advance_at(SystemTime::now()).unwrap();
+        assert_eq!(next_hourly, now + Duration::from_secs(2 * 3600));
+        
+        // Test daily
+        let mut daily_sched = Schedule {
+            schedule_type: ScheduleType::Recurring(RecurringSchedule::new(RecurringInterval::Daily(1), now)),
+            max_runs: None,
+            run_count: 0,
+            next_run: Some(now),
+        };
+        let next_daily = daily_sched.advance_at(SystemTime::now()).unwrap();
+        assert_eq!(next_daily, now + Duration::from_secs(86400));
+        
+        // Test custom expression
+        let mut custom_sched = Schedule {
+            schedule_type: ScheduleType::Recurring(RecurringSchedule::new(
+                RecurringInterval::Custom { 
+                    expression: "weekly".to_string(), 
+                    frequency: 1 
+                },
+                now,
+            )),
+            max_runs: None,
+            run_count: 0,
+            next_run: Some(now),
+        };
+        let next_custom = custom_sched.advance_at(SystemTime::now()).unwrap();
+        assert_eq!(next_custom, now + Duration::from_secs(7 * 86400));
+    }
+    
+    #[test]
+    fn test_max_runs_limit() {
+        let now = SystemTime::now();
+        let mut sched = Schedule {
+            schedule_type: ScheduleType::Recurring(RecurringSchedule::new(RecurringInterval::Secondly(1), now)),
+            max_runs: Some(3),
+            run_count: 3,  // Already reached max_runs
+            next_run: Some(now),
+        };
+        
+        let next_run = sched.advance_at(SystemTime::now());
+        assert!(next_run.is_none());
+    }
+    
+    #[test]
+    fn test_peek_next_run() {
+        let now = SystemTime::now();
+        
+        // Test recurring schedule
+        let recurring_sched = Schedule {
+            schedule_type: ScheduleType::Recurring(RecurringSchedule::new(RecurringInterval::Secondly(1), now + Duration::from_secs(5))),
+            max_runs: None,
+            run_count: 0,
+            next_run: Some(now + Duration::from_secs(5)),
+        };
+        
+        let peeked = recurring_sched.peek_next_run();
+        assert_eq!(peeked.unwrap(), now + Duration::from_secs(5));
+        
+        // Test once schedule
+        let once_sched = Schedule {
+            schedule_type: ScheduleType::Once(now),
+            max_runs: Some(1),
+            run_count: 0,
+            next_run: Some(now),
+        };
+        
+        let peeked_once = once_sched.peek_next_run();
+        assert!(peeked_once.is_none());
+    }
+    
+    #[test]
+    fn test_panic_threshold_pauses_job() {
+        use std::sync::{Arc, Mutex};
+
+        let start = SystemTime::now();
+        let runs = Arc::new(Mutex::new(0));
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let runs_clone = Arc::clone(&runs);
+        let errors_clone = Arc::clone(&errors);
+        let job = JobBuilder::new("crash-loop")
+            .recurring(RecurringInterval::Minutely(1), Some(ScheduleTime::At(start)))
+            .add_handler(move || {
+                *runs_clone.lock().unwrap() += 1;
+                panic!("boom");
+            })
+            .on_error(move |err| errors_clone.lock().unwrap().push(err.to_string()))
+            .build();
+        let id = job.id;
+        let mut scheduler = Scheduler::new().panic_threshold(2);
+        scheduler.add_job(job).unwrap();
+
+        scheduler.run_pending_at(start).unwrap();
+        assert!(!scheduler.jobs[0].paused);
+        scheduler.run_pending_at(start + Duration::from_secs(60)).unwrap();
+        assert!(scheduler.jobs[0].paused);
+        {
+            let errors = errors.lock().unwrap();
+            assert_eq!(errors.len(), 3);
+            assert!(errors[2].contains("circuit open"));
+        }
+
+        // A paused job neither runs nor counts as upcoming
+        scheduler.run_pending_at(start + Duration::from_secs(120)).unwrap();
+        assert_eq!(*runs.lock().unwrap(), 2);
+        assert_eq!(scheduler.next_run(), None);
+
+        scheduler.resume_job(id).unwrap();
+        scheduler.run_pending_at(start + Duration::from_secs(180)).unwrap();
+        assert_eq!(*runs.lock().unwrap(), 3);
+        assert!(!scheduler.jobs[0].paused);
+        assert!(matches!(scheduler.resume_job(Uuid::new_v4()), Err(JobSchedulerError::JobNotFound(_))));
+    }
+
+    #[test]
+    fn test_after_job_propagation() {
+        let start = SystemTime::now();
+        let upstream = JobBuilder::new("extract")
+            .recurring(RecurringInterval::Minutely(1), Some(ScheduleTime::At(start)))
+            .add_handler(dummy_handler)
+            .build();
+        let downstream = JobBuilder::new("report")
+            .after_job(upstream.id, Duration::from_secs(10))
+            .add_handler(dummy_handler)
+            .build();
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(downstream).unwrap();
+        scheduler.add_job(upstream).unwrap();
+        assert_eq!(scheduler.jobs[0].next_run, None);
+
+        // Each run of the upstream job schedules the downstream one
+        scheduler.run_pending_at(start).unwrap();
+        assert_eq!(scheduler.jobs[0].next_run, Some(start + Duration::from_secs(10)));
+        scheduler.run_pending_at(start + Duration::from_secs(10)).unwrap();
+        assert_eq!(scheduler.jobs[0].last_run, Some(start + Duration::from_secs(10)));
+        assert_eq!(scheduler.jobs[0].next_run, None);
+
+        scheduler.run_pending_at(start + Duration::from_secs(60)).unwrap();
+        assert_eq!(scheduler.jobs[0].next_run, Some(start + Duration::from_secs(70)));
+    }
+
+    #[test]
+    fn test_jobs_by_state() {
+        let start = SystemTime::now();
+        let mut scheduler = Scheduler::new();
+        let once = JobBuilder::new("once")
+            .once(ScheduleTime::At(start))
+            .add_handler(dummy_handler)
+            .build();
+        let recurring = JobBuilder::new("recurring")
+            .recurring(RecurringInterval::Hourly(1), None)
+            .add_handler(dummy_handler)
+            .build();
+        let paused = JobBuilder::new("paused")
+            .recurring(RecurringInterval::Hourly(1), None)
+            .add_handler(dummy_handler)
+            .build();
+        let (once_id, recurring_id, paused_id) = (once.id, recurring.id, paused.id);
+        scheduler.add_job(once).unwrap();
+        scheduler.add_job(recurring).unwrap();
+        scheduler.add_job(paused).unwrap();
+        scheduler.pause_job(paused_id).unwrap();
+        scheduler.run_pending_at(start).unwrap();
+
+        let by_state = scheduler.jobs_by_state();
+        assert_eq!(by_state[&JobState::Active], vec![recurring_id]);
+        assert_eq!(by_state[&JobState::Paused], vec![paused_id]);
+        assert_eq!(by_state[&JobState::Completed], vec![once_id]);
+    }
+
+    #[test]
+    fn test_on_complete() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let start = SystemTime::now();
+        let capped_done = Arc::new(AtomicU32::new(0));
+        let endless_done = Arc::new(AtomicU32::new(0));
+        let capped_clone = Arc::clone(&capped_done);
+        let endless_clone = Arc::clone(&endless_done);
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(JobBuilder::new("migration")
+            .recurring(RecurringInterval::Minutely(1), Some(ScheduleTime::At(start)))
+            .max_repeat(3)
+            .add_handler(dummy_handler)
+            .on_complete(move || { capped_clone.fetch_add(1, Ordering::SeqCst); })
+            .build()).unwrap();
+        scheduler.add_job(JobBuilder::new("heartbeat")
+            .recurring(RecurringInterval::Minutely(1), Some(ScheduleTime::At(start)))
+            .add_handler(dummy_handler)
+            .on_complete(move || { endless_clone.fetch_add(1, Ordering::SeqCst); })
+            .build()).unwrap();
+
+        for minute in 0..5 {
+            scheduler.run_pending_at(start + Duration::from_secs(60 * minute)).unwrap();
+            let expected = if minute >= 2 { 1 } else { 0 };
+            assert_eq!(capped_done.load(Ordering::SeqCst), expected);
+        }
+        assert_eq!(endless_done.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_merge() {
+        let shared_id = Uuid::new_v4();
+        let mut billing = Scheduler::new();
+        billing.add_job(JobBuilder::new("invoice")
+            .id(shared_id)
+            .recurring(RecurringInterval::Daily(1), None)
+            .add_handler(dummy_handler)
+            .build()).unwrap();
+        let mut reports = Scheduler::new();
+        reports.add_job(JobBuilder::new("summary")
+            .recurring(RecurringInterval::Daily(1), None)
+            .add_handler(dummy_handler)
+            .build()).unwrap();
+
+        billing.merge(reports).unwrap();
+        let names: Vec<_> = billing.jobs.iter().map(|job| job.name.as_deref()).collect();
+        assert_eq!(names, vec![Some("invoice"), Some("summary")]);
+
+        // A clashing id rejects the whole merge
+        let mut clashing = Scheduler::new();
+        clashing.add_job(JobBuilder::new("other")
+            .recurring(RecurringInterval::Daily(1), None)
+            .add_handler(dummy_handler)
+            .build()).unwrap();
+        clashing.add_job(JobBuilder::new("copy")
+            .id(shared_id)
+            .recurring(RecurringInterval::Daily(1), None)
+            .add_handler(dummy_handler)
+            .build()).unwrap();
+        assert!(matches!(billing.merge(clashing), Err(JobSchedulerError::InvalidSchedule(_))));
+        assert_eq!(billing.jobs.len(), 2);
+
+        // A failed run still in flight does not stop the jobs moving
+        let mut failing = Scheduler::new();
+        failing.add_job(JobBuilder::new("fails")
+            .now_once()
+            .dedicated_thread()
+            .add_handler(|| panic!("run failed"))
+            .build()).unwrap();
+        failing.add_job(JobBuilder::new("pending")
+            .recurring(RecurringInterval::Daily(1), None)
+            .add_handler(dummy_handler)
+            .build()).unwrap();
+        failing.run_pending().unwrap();
+        billing.merge(failing).unwrap();
+        assert_eq!(billing.jobs.len(), 4);
+        assert_eq!(billing.jobs[2].total_failures(), 1);
+    }
+
+    #[test]
+    fn test_with_max_jobs() {
+        let job = || JobBuilder::new("job")
+            .recurring(RecurringInterval::Hourly(1), None)
+            .add_handler(dummy_handler)
+            .build();
+        let mut scheduler = Scheduler::with_max_jobs(2);
+        scheduler.add_job(job()).unwrap();
+        scheduler.add_job(job()).unwrap();
+        let full = scheduler.add_job(job());
+        assert!(matches!(full, Err(JobSchedulerError::InvalidSchedule(msg)) if msg == "capacity exceeded"));
+
+        let template = JobTemplate::from(JobBuilder::new_unnamed().recurring(RecurringInterval::Hourly(1), None));
+        let mut scheduler = Scheduler::with_max_jobs(2);
+        assert!(scheduler.add_from_template(&template, &["a", "b", "c"], |_| || {}).is_err());
+        assert!(scheduler.jobs.is_empty());
+    }
+
+    #[test]
+    fn test_format_sleep() {
+        assert_eq!(format_sleep(Duration::from_millis(3_661_250)), "1h 1m 1s");
+        assert_eq!(format_sleep(Duration::from_millis(250)), "250ms");
+        assert_eq!(format_sleep(Duration::ZERO), "0s");
+    }
+
+    #[test]
+    fn test_on_idle() -> Result<(), JobSchedulerError> {
+        use std::sync::{Arc, Mutex};
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let job_events = Arc::clone(&events);
+        let idle_events = Arc::clone(&events);
+        let mut scheduler = Scheduler::new();
+        scheduler.on_idle(move || idle_events.lock().unwrap().push("idle"));
+        scheduler.add_job(JobBuilder::new("one-off")
+            .once(ScheduleTime::At(SystemTime::now()))
+            .add_handler(move || job_events.lock().unwrap().push("run"))
+            .build())?;
+
+        scheduler.run_non_blocking()?;
+        assert_eq!(*events.lock().unwrap(), vec!["run", "idle"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_job_added_through_handle_wakes_run_loop() -> Result<(), JobSchedulerError> {
+        use std::sync::{Arc, Mutex};
+
+        // Poll interval is longer than the whole test so only the handle can wake the loop early
+        let mut scheduler = Scheduler::new().max_poll_interval(Duration::from_secs(10));
+        let keep_alive = JobBuilder::new("keep-alive")
+            .once(ScheduleTime::Delay(Duration::from_secs(2)))
+            .add_handler(dummy_handler)
+            .build();
+        scheduler.add_job(keep_alive)?;
+
+        let handle = scheduler.handle();
+        let fired_at = Arc::new(Mutex::new(None));
+        let fired_at_clone = Arc::clone(&fired_at);
+        let due = SystemTime::now() + Duration::from_millis(300);
+        let adder = std::thread::spawn(move || {
+            sleep(Duration::from_millis(100));
+            let job = JobBuilder::new("late-added")
+                .once(ScheduleTime::At(due))
+                .add_handler(move || {
+                    *fired_at_clone.lock().unwrap() = Some(SystemTime::now());
+                })
+                .build();
+            handle.add_job(job)
+        });
+
+        scheduler.run_non_blocking()?;
+        adder.join().unwrap()?;
+
+        let fired_at = fired_at.lock().unwrap().expect("late-added job should have fired");
+        assert!(fired_at >= due);
+        assert!(fired_at.duration_since(due).unwrap() < Duration::from_millis(200));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cron_schedule() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        let cron_str = "0 0 * * * *"; // Run at midnight every day
+        
+        let job = JobBuilder::new("cron-job")
+            .cron(cron_str)
+            .add_handler(dummy_handler)
+            .build();
+            
+        scheduler.add_job(job)?;
+        
+        assert!(scheduler.next_run().is_some());
+        
+        Ok(())
+    }
+
+    #[test]
+    fn test_random_schedule() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        
+        // Create random scheduled job with fixed times for predictable testing
+        let start = SystemTime::now() + Duration::from_secs(1);
+        let end = SystemTime::now() + Duration::from_secs(5);
+        
+        let job = JobBuilder::new("random-job")
+            .random(ScheduleTime::At(start), ScheduleTime::At(end))
+            .add_handler(dummy_handler)
+            .build();
+            
+        scheduler.add_job(job)?;
+        
+        assert_eq!(scheduler.jobs.len(), 1);
+        assert!(scheduler.jobs[0].next_run.is_some());
+        
+        // The random time should be between start and end
+        let next_run = scheduler.jobs[0].next_run.unwrap();
+        assert!(next_run >= start && next_run <= end);
+        
+        Ok(())
+    }
+
+    #[test]
+    fn test_replace_handler() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let mut scheduler = Scheduler::new();
+        let job = JobBuilder::new("rebind")
+            .once(ScheduleTime::Delay(Duration::from_secs(60)))
+            .add_handler(dummy_handler)
+            .build();
+        let id = job.id;
+        scheduler.add_job(job).unwrap();
+
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&calls);
+        scheduler.replace_handler(id, move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }).unwrap();
+        scheduler.jobs[0].run().unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let missing = scheduler.replace_handler(Uuid::new_v4(), dummy_handler);
+        assert!(matches!(missing, Err(JobSchedulerError::JobNotFound(_))));
+    }
+
+    #[test]
+    fn test_max_catchup() {
+        let now = SystemTime::now();
+        // A secondly job that missed a whole day of runs
+        let job = JobBuilder::new("clock-jump")
+            .recurring(RecurringInterval::Secondly(1), Some(ScheduleTime::At(now - Duration::from_secs(86400))))
+            .add_handler(dummy_handler)
+            .build();
+        let mut scheduler = Scheduler::new().max_catchup(Duration::from_secs(10));
+        scheduler.add_job(job).unwrap();
+
+        scheduler.run_pending().unwrap();
+        // Only the last ten seconds of missed runs remain to be caught up
+        let next_run = scheduler.jobs[0].next_run.unwrap();
+        assert!(next_run + Duration::from_secs(10) >= now);
+        assert!(next_run <= now);
+        let mut sweeps = 1;
+        while scheduler.jobs[0].next_run.unwrap() <= SystemTime::now() {
+            scheduler.run_pending().unwrap();
+            sweeps += 1;
+        }
+        assert!(sweeps <= 12);
+    }
+
+    #[test]
+    fn test_cron_once() {
+        let job = JobBuilder::new("cron-once")
+            .cron_once("* * * * * * *")
+            .add_handler(dummy_handler)
+            .build();
+        assert_eq!(job.schedules[0].max_runs, Some(1));
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(job).unwrap();
+
+        scheduler.run_pending_at(SystemTime::now() + Duration::from_millis(1100)).unwrap();
+        assert_eq!(scheduler.jobs[0].total_run_count(), 1);
+        assert!(scheduler.jobs[0].next_run.is_none());
+    }
+
+    #[test]
+    fn test_add_job_rejects_duplicate_id() {
+        let id = Uuid::new_v4();
+        let job = |name: &str| JobBuilder::new(name)
+            .id(id)
+            .once(ScheduleTime::Delay(Duration::from_secs(60)))
+            .add_handler(dummy_handler)
+            .build();
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(job("first")).unwrap();
+        assert_eq!(scheduler.jobs[0].id, id);
+        let duplicate = scheduler.add_job(job("second"));
+        assert!(matches!(duplicate, Err(JobSchedulerError::InvalidSchedule(_))));
+        assert_eq!(scheduler.jobs.len(), 1);
+    }
+
+    #[test]
+    fn test_find_jobs() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(JobBuilder::new("bounded")
+            .recurring(RecurringInterval::Hourly(1), None)
+            .max_repeat(3)
+            .add_handler(dummy_handler)
+            .build())?;
+        scheduler.add_job(JobBuilder::new("unbounded")
+            .recurring(RecurringInterval::Hourly(1), None)
+            .add_handler(dummy_handler)
+            .build())?;
+        scheduler.add_job(JobBuilder::new("one-off")
+            .once(ScheduleTime::Delay(Duration::from_secs(60)))
+            .add_handler(dummy_handler)
+            .build())?;
+
+        // Recurring jobs with no run limit that have not run yet
+        let found = scheduler.find_jobs(|job| {
+            job.last_run.is_none()
+                && job.effective_max_runs().is_none()
+                && job.schedules.iter().all(|sched| matches!(sched.schedule_type, ScheduleType::Recurring(_)))
+        });
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name.as_deref(), Some("unbounded"));
+        assert!(scheduler.find_jobs(|_| false).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut scheduler = Scheduler::new();
+        let job = JobBuilder::new("report")
+            .recurring(RecurringInterval::Hourly(1), None)
+            .cron("0 0 * * * * *")
+            .add_handler(dummy_handler)
+            .build();
+        let id = job.id;
+        let next_run = job.next_run;
+        scheduler.add_job(job).unwrap();
+
+        let snapshot = scheduler.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].id, id);
+        assert_eq!(snapshot[0].name.as_deref(), Some("report"));
+        assert_eq!(snapshot[0].next_run, next_run);
+        assert_eq!(snapshot[0].last_run, None);
+        assert_eq!(snapshot[0].run_count, 0);
+        assert_eq!(snapshot[0].schedule, "every 1h; cron 0 0 * * * * *");
+
+        let json = serde_json::to_value(&snapshot[0]).unwrap();
+        assert_eq!(json["name"], "report");
+        assert_eq!(json["id"], id.to_string());
+    }
+
+    #[test]
+    fn test_dedicated_thread() {
+        use std::sync::{Arc, Mutex};
+
+        let thread_names = Arc::new(Mutex::new(Vec::new()));
+        let names = Arc::clone(&thread_names);
+        let job = JobBuilder::new("named-job")
+            .recurring(RecurringInterval::Secondly(0), Some(ScheduleTime::At(SystemTime::now())))
+            .max_repeat(2)
+            .dedicated_thread()
+            .add_handler(move || {
+                sleep(Duration::from_millis(50));
+                names.lock().unwrap().push(std::thread::current().name().map(String::from));
+            })
+            .build();
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(job).unwrap();
+
+        scheduler.run_pending().unwrap();
+        assert_eq!(scheduler.in_flight.len(), 1);
+        assert!(scheduler.jobs[0].handler.is_none());
+        // Still running, so the second run must wait
+        scheduler.run_pending().unwrap();
+        assert_eq!(scheduler.jobs[0].total_run_count(), 1);
+
+        scheduler.run_non_blocking().unwrap();
+        assert!(scheduler.in_flight.is_empty());
+        assert!(scheduler.jobs[0].handler.is_some());
+        assert!(scheduler.jobs[0].last_run.is_some());
+        assert_eq!(*thread_names.lock().unwrap(), vec![Some("named-job".to_string()); 2]);
+    }
+
+    #[test]
+    fn test_dedicated_thread_in_flight() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new().max_poll_interval(Duration::from_secs(30));
+        // Not a valid thread name, so the thread is named after the id
+        let job = JobBuilder::new("nul\0name")
+            .recurring(RecurringInterval::Secondly(0), Some(ScheduleTime::At(SystemTime::now())))
+            .dedicated_thread()
+            .add_handler(|| sleep(Duration::from_millis(200)))
+            .build();
+        let id = job.id;
+        scheduler.add_job(job)?;
+        // The job is overdue again but still running, so the loop does not spin on it
+        let wait = scheduler.tick()?.unwrap();
+        assert!(wait > Duration::from_secs(29));
+        assert_eq!(scheduler.in_flight.len(), 1);
+        assert_eq!(scheduler.in_flight[0].1.thread().name(), Some(id.to_string().as_str()));
+        assert!(scheduler.shutdown(Duration::from_secs(1)).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dedicated_thread_survives_panic() -> Result<(), JobSchedulerError> {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&calls);
+        let start = SystemTime::now();
+        let mut scheduler = Scheduler::new();
+        scheduler.on_any_error(|_, _| {});
+        scheduler.add_job(JobBuilder::new("flaky")
+            .recurring(RecurringInterval::Secondly(1), Some(ScheduleTime::At(start)))
+            .dedicated_thread()
+            .add_handler(move || {
+                assert!(counter.fetch_add(1, Ordering::SeqCst) > 0, "first run panics");
+            })
+            .build())?;
+        let wait_for_worker = |scheduler: &Scheduler| {
+            while scheduler.in_flight.iter().any(|(_, worker)| !worker.is_finished()) {
+                sleep(Duration::from_millis(5));
+            }
+        };
+
+        scheduler.run_pending_at(start)?;
+        wait_for_worker(&scheduler);
+        // The panicked run hands its handler back, so the next run still fires
+        scheduler.run_pending_at(start + Duration::from_secs(1))?;
+        wait_for_worker(&scheduler);
+        scheduler.run_pending_at(start + Duration::from_secs(1))?;
+        let job = &scheduler.jobs[0];
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(job.handler.is_some());
+        assert_eq!(job.total_failures(), 1);
+        assert_eq!(job.recent_runs().last().unwrap().outcome, RunOutcome::Succeeded);
+        Ok(())
+    }
+
+    #[test]
+    fn test_resume_dedicated_job_after_circuit_opens() -> Result<(), JobSchedulerError> {
+        let start = SystemTime::now();
+        let mut scheduler = Scheduler::new().panic_threshold(1);
+        let job = JobBuilder::new("always_panics")
+            .recurring(RecurringInterval::Secondly(1), Some(ScheduleTime::At(start)))
+            .dedicated_thread()
+            .add_handler(|| panic!("handler failed"))
+            .build();
+        let id = job.id;
+        scheduler.add_job(job)?;
+        scheduler.run_pending_at(start)?;
+        while scheduler.in_flight.iter().any(|(_, worker)| !worker.is_finished()) {
+            sleep(Duration::from_millis(5));
+        }
+        assert!(matches!(
+            scheduler.run_pending_at(start + Duration::from_secs(1)),
+            Err(JobSchedulerError::ExecutionFailed(_))
+        ));
+        assert!(scheduler.jobs[0].paused);
+        assert!(scheduler.jobs[0].handler.is_some());
+
+        scheduler.resume_job(id)?;
+        scheduler.run_pending_at(start + Duration::from_secs(2))?;
+        assert_eq!(scheduler.in_flight.len(), 1);
+
+        // Every finished run is recorded before the failures are returned
+        let mut scheduler = Scheduler::new();
+        for name in ["bad-1", "bad-2"] {
+            scheduler.add_job(JobBuilder::new(name)
+                .once(ScheduleTime::At(start))
+                .dedicated_thread()
+                .add_handler(|| panic!("handler failed"))
+                .build())?;
+        }
+        scheduler.run_pending_at(start)?;
+        while scheduler.in_flight.iter().any(|(_, worker)| !worker.is_finished()) {
+            sleep(Duration::from_millis(5));
+        }
+        match scheduler.run_pending_at(start) {
+            Err(JobSchedulerError::ExecutionFailed(msg)) => assert!(msg.starts_with("2 jobs failed")),
+            other => panic!("expected ExecutionFailed, got {:?}", other),
+        }
+        assert!(scheduler.jobs.iter().all(|job| job.handler.is_some() && job.total_failures() == 1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_history_limit() {
+        let job = JobBuilder::new("history")
+            .burst(5)
+            .add_handler(dummy_handler)
+            .build();
+        let mut scheduler = Scheduler::new().history_limit(3);
+        scheduler.add_job(job).unwrap();
+
+        let start = SystemTime::now();
+        let starts: Vec<SystemTime> = (0..5).map(|step| start + Duration::from_secs(step)).collect();
+        for &now in &starts {
+            scheduler.run_pending_at(now).unwrap();
+        }
+        let recent = scheduler.jobs[0].recent_runs();
+        assert_eq!(recent.len(), 3);
+        // The oldest records were evicted
+        let kept: Vec<SystemTime> = recent.iter().map(|record| record.started).collect();
+        assert_eq!(kept, starts[2..]);
+        assert!(recent.iter().all(|record| record.outcome == RunOutcome::Succeeded));
+    }
+
+    #[test]
+    fn test_set_next_run() {
+        let start = SystemTime::now() + Duration::from_secs(3600);
+        let job = JobBuilder::new("override")
+            .recurring(RecurringInterval::Hourly(1), Some(ScheduleTime::At(start)))
+            .add_handler(dummy_handler)
+            .build();
+        let id = job.id;
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(job).unwrap();
+
+        scheduler.set_next_run(id, SystemTime::now()).unwrap();
+        scheduler.run_pending().unwrap();
+        assert!(scheduler.jobs[0].last_run.is_some());
+        // The schedule itself did not fire, so the normal cadence resumes
+        assert_eq!(scheduler.jobs[0].schedules[0].run_count, 0);
+        assert_eq!(scheduler.jobs[0].next_run, Some(start));
+
+        let missing = scheduler.set_next_run(Uuid::new_v4(), SystemTime::now());
+        assert!(matches!(missing, Err(JobSchedulerError::JobNotFound(_))));
+    }
+
+    #[test]
+    fn test_add_from_template() {
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let template = JobTemplate::from(JobBuilder::new_unnamed()
+            .once(ScheduleTime::At(SystemTime::now())));
+        let mut scheduler = Scheduler::new();
+        let ids = scheduler.add_from_template(&template, &["tenant-a", "tenant-b"], |tenant| {
+            let seen = Arc::clone(&seen);
+            let tenant = tenant.to_string();
+            move || seen.lock().unwrap().push(tenant.clone())
+        }).unwrap();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(scheduler.jobs[1].id, ids[1]);
+
+        scheduler.run_pending().unwrap();
+        assert_eq!(*seen.lock().unwrap(), vec!["tenant-a".to_string(), "tenant-b".to_string()]);
+
+        // Nothing is added if the template cannot produce a runnable job
+        let empty = JobTemplate::from(JobBuilder::new_unnamed());
+        let result = scheduler.add_from_template(&empty, &["tenant-c"], |_| || {});
+        assert!(matches!(result, Err(JobSchedulerError::MissingSchedule)));
+        assert_eq!(scheduler.jobs.len(), 2);
+    }
+
+    #[test]
+    fn test_retry_with_backoff() {
+        use std::sync::{Arc, Mutex};
+        use crate::job::BackoffStrategy;
+
+        let start = SystemTime::now();
+        let calls = Arc::new(Mutex::new(0));
+        let errors = Arc::new(Mutex::new(0));
+        let calls_clone = Arc::clone(&calls);
+        let errors_clone = Arc::clone(&errors);
+        let mut flaky = JobBuilder::new("flaky")
+            .once(ScheduleTime::At(start))
+            .retry_with(3, BackoffStrategy::Exponential { base: Duration::from_secs(10), max: Duration::from_secs(60) })
+            .on_error(move |_| *errors_clone.lock().unwrap() += 1)
+            .build();
+        flaky.handler = Some(Box::new(move |_, _| {
+            let mut calls = calls_clone.lock().unwrap();
+            *calls += 1;
+            if *calls < 3 {
+                Err(JobSchedulerError::ExecutionFailed("not yet".to_string()))
+            } else {
+                Ok(())
+            }
+        }));
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(flaky).unwrap();
+
+        scheduler.run_pending_at(start).unwrap();
+        assert_eq!(scheduler.jobs[0].next_run, Some(start + Duration::from_secs(10)));
+        scheduler.run_pending_at(start + Duration::from_secs(5)).unwrap();
+        assert_eq!(*calls.lock().unwrap(), 1);
+        scheduler.run_pending_at(start + Duration::from_secs(10)).unwrap();
+        assert_eq!(scheduler.jobs[0].next_run, Some(start + Duration::from_secs(30)));
+        scheduler.run_pending_at(start + Duration::from_secs(30)).unwrap();
+
+        // The third attempt succeeded, so the failures were never reported
+        assert_eq!(*calls.lock().unwrap(), 3);
+        assert_eq!(*errors.lock().unwrap(), 0);
+        assert_eq!(scheduler.jobs[0].next_run, None);
+        assert_eq!(scheduler.jobs[0].total_failures(), 2);
+        let outcomes: Vec<&RunOutcome> = scheduler.jobs[0].recent_runs().iter().map(|run| &run.outcome).collect();
+        assert_eq!(outcomes.len(), 3);
+        assert_eq!(outcomes[2], &RunOutcome::Succeeded);
+
+        // Once attempts run out the failure is reported and the schedule moves on
+        let failures = Arc::new(Mutex::new(0));
+        let failures_clone = Arc::clone(&failures);
+        scheduler.add_job(JobBuilder::new("hopeless")
+            .once(ScheduleTime::At(start))
+            // Fails every time, as no context is attached
+            .add_handler_with_ctx(|_: &u32| {})
+            .retry_with(2, BackoffStrategy::Fixed(Duration::from_secs(1)))
+            .on_error(move |_| *failures_clone.lock().unwrap() += 1)
+            .build()).unwrap();
+        scheduler.run_pending_at(start).unwrap();
+        assert_eq!(*failures.lock().unwrap(), 0);
+        scheduler.run_pending_at(start + Duration::from_secs(1)).unwrap();
+        assert_eq!(*failures.lock().unwrap(), 1);
+        assert_eq!(scheduler.jobs[1].next_run, None);
+    }
+
+    #[test]
+    fn test_run_if_guard() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+        let start = SystemTime::now();
+        let allowed = Arc::new(AtomicBool::new(false));
+        let runs = Arc::new(AtomicU32::new(0));
+        let allowed_clone = Arc::clone(&allowed);
+        let runs_clone = Arc::clone(&runs);
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(JobBuilder::new("guarded")
+            .recurring(RecurringInterval::Minutely(1), Some(ScheduleTime::At(start)))
+            .run_if(move || allowed_clone.load(Ordering::SeqCst))
+            .add_handler(move || { runs_clone.fetch_add(1, Ordering::SeqCst); })
+            .build()).unwrap();
+
+        // A false guard skips the run but still moves to the next occurrence
+        scheduler.run_pending_at(start).unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+        assert_eq!(scheduler.jobs[0].last_run, None);
+        assert_eq!(scheduler.jobs[0].total_run_count(), 0);
+        assert_eq!(scheduler.jobs[0].next_run, Some(start + Duration::from_secs(60)));
+        assert_eq!(scheduler.jobs[0].recent_runs()[0].outcome, RunOutcome::Skipped);
+
+        allowed.store(true, Ordering::SeqCst);
+        scheduler.run_pending_at(start + Duration::from_secs(60)).unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(scheduler.jobs[0].recent_runs()[1].outcome, RunOutcome::Succeeded);
+    }
+
+    #[test]
+    fn test_tick() {
+        let mut scheduler = Scheduler::new().max_poll_interval(Duration::from_secs(30));
+        assert_eq!(scheduler.tick().unwrap(), None);
+
+        scheduler.add_job(JobBuilder::new("now")
+            .once(ScheduleTime::At(SystemTime::now()))
+            .add_handler(dummy_handler)
+            .build()).unwrap();
+        scheduler.add_job(JobBuilder::new("soon")
+            .once(ScheduleTime::Delay(Duration::from_secs(10)))
+            .add_handler(dummy_handler)
+            .build()).unwrap();
+        let wait = scheduler.tick().unwrap().unwrap();
+        assert!(scheduler.jobs[0].last_run.is_some());
+        assert!(wait > Duration::from_secs(9) && wait <= Duration::from_secs(10));
+
+        // Long waits are capped at the poll interval
+        scheduler.set_next_run(scheduler.jobs[1].id, SystemTime::now() + Duration::from_secs(3600)).unwrap();
+        assert_eq!(scheduler.tick().unwrap(), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_tick_waits_for_dependency() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new().max_poll_interval(Duration::from_secs(30));
+        let upstream = JobBuilder::new("upstream")
+            .once(ScheduleTime::Delay(Duration::from_secs(10)))
+            .add_handler(dummy_handler)
+            .build();
+        // Due now, but held back until upstream has run
+        scheduler.add_job(JobBuilder::new("downstream")
+            .once(ScheduleTime::At(SystemTime::now()))
+            .after(upstream.id)
+            .add_handler(dummy_handler)
+            .build())?;
+        scheduler.add_job(upstream)?;
+        let wait = scheduler.tick()?.unwrap();
+        assert!(scheduler.jobs[0].last_run.is_none());
+        assert!(wait > Duration::from_secs(9) && wait <= Duration::from_secs(10));
+
+        // With nothing else to wait for, the held-back job is checked every poll interval
+        scheduler.jobs[1].paused = true;
+        let wait = scheduler.tick()?.unwrap();
+        assert!(wait > Duration::from_secs(29) && wait <= Duration::from_secs(30));
+
+        // The run loop sleeps until upstream is due, then runs both
+        let mut scheduler = Scheduler::new().max_poll_interval(Duration::from_millis(50));
+        let upstream = JobBuilder::new("upstream")
+            .once(ScheduleTime::Delay(Duration::from_millis(200)))
+            .add_handler(dummy_handler)
+            .build();
+        scheduler.add_job(JobBuilder::new("downstream")
+            .once(ScheduleTime::At(SystemTime::now()))
+            .after(upstream.id)
+            .add_handler(dummy_handler)
+            .build())?;
+        scheduler.add_job(upstream)?;
+        scheduler.run_non_blocking()?;
+        assert!(scheduler.jobs[0].last_run > scheduler.jobs[1].last_run);
+        Ok(())
+    }
+
+    #[test]
+    fn test_snooze() {
+        use std::sync::{Arc, Mutex};
+
+        let start = SystemTime::now();
+        let runs = Arc::new(Mutex::new(0));
+        let runs_clone = Arc::clone(&runs);
+        let job = JobBuilder::new("snoozed")
+            .recurring(RecurringInterval::Minutely(1), Some(ScheduleTime::At(start)))
+            .add_handler(move || *runs_clone.lock().unwrap() += 1)
+            .build();
+        let id = job.id;
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(job).unwrap();
+
+        let until = start + Duration::from_secs(330);
+        scheduler.snooze(id, until).unwrap();
+        for secs in [0, 60, 120, 300, 329] {
+            scheduler.run_pending_at(start + Duration::from_secs(secs)).unwrap();
+        }
+        assert_eq!(*runs.lock().unwrap(), 0);
+
+        // Normal scheduling resumes at the first occurrence after `until`
+        assert_eq!(scheduler.jobs[0].next_run, Some(start + Duration::from_secs(360)));
+        scheduler.run_pending_at(start + Duration::from_secs(360)).unwrap();
+        assert_eq!(*runs.lock().unwrap(), 1);
+
+        // Snoozing to before the next run changes nothing
+        let next_run = scheduler.jobs[0].next_run;
+        scheduler.snooze(id, start + Duration::from_secs(400)).unwrap();
+        assert_eq!(scheduler.jobs[0].next_run, next_run);
+
+        let missing = scheduler.snooze(Uuid::new_v4(), until);
+        assert!(matches!(missing, Err(JobSchedulerError::JobNotFound(_))));
+    }
+
+    #[test]
+    fn test_with_capacity_and_reserve() {
+        let mut scheduler = Scheduler::with_capacity(64);
+        assert!(scheduler.jobs.capacity() >= 64);
+        assert!(scheduler.jobs.is_empty());
+        scheduler.reserve(200);
+        assert!(scheduler.jobs.capacity() >= 200);
+    }
+
+    #[test]
+    fn test_only_between_confines_recurring_job() -> Result<(), JobSchedulerError> {
+        use chrono::NaiveTime;
+
+        let at = |s: &str| SystemTime::from(chrono::DateTime::parse_from_rfc3339(s).unwrap());
+        let hours = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        let mut scheduler = Scheduler::new();
+        let business = JobBuilder::new("business-hours")
+            .recurring(RecurringInterval::Minutely(30), Some(ScheduleTime::At(at("2030-01-01T16:00:00Z"))))
+            .only_between(hours(9), hours(17))
+            .add_handler(dummy_handler)
+            .build();
+        // Starts outside its window, so waits for the evening opening
+        let overnight = JobBuilder::new("overnight")
+            .recurring(RecurringInterval::Hourly(1), Some(ScheduleTime::At(at("2030-01-01T03:00:00Z"))))
+            .only_between(hours(22), hours(2))
+            .add_handler(dummy_handler)
+            .build();
+        assert_eq!(overnight.next_run, Some(at("2030-01-01T22:00:00Z")));
+        scheduler.add_job(business)?;
+        scheduler.add_job(overnight)?;
+
+        scheduler.run_pending_at(at("2030-01-01T16:00:00Z"))?;
+        assert_eq!(scheduler.jobs[0].next_run, Some(at("2030-01-01T16:30:00Z")));
+        scheduler.run_pending_at(at("2030-01-01T16:30:00Z"))?;
+        // 17:00 is outside the window, so the next run is the following morning
+        assert_eq!(scheduler.jobs[0].next_run, Some(at("2030-01-02T09:00:00Z")));
+
+        // The overnight window spans midnight
+        for hour in ["22", "23"] {
+            scheduler.run_pending_at(at(&format!("2030-01-01T{}:00:00Z", hour)))?;
+        }
+        assert_eq!(scheduler.jobs[1].next_run, Some(at("2030-01-02T00:00:00Z")));
+        scheduler.run_pending_at(at("2030-01-02T00:00:00Z"))?;
+        scheduler.run_pending_at(at("2030-01-02T01:00:00Z"))?;
+        assert_eq!(scheduler.jobs[1].next_run, Some(at("2030-01-02T22:00:00Z")));
+
+        // Runs skipped beyond the catch-up limit do not land outside the window
+        let mut scheduler = Scheduler::new().max_catchup(Duration::from_secs(3600));
+        scheduler.add_job(JobBuilder::new("business-hours")
+            .recurring(RecurringInterval::Minutely(30), Some(ScheduleTime::At(at("2030-01-01T16:00:00Z"))))
+            .only_between(hours(9), hours(17))
+            .add_handler(dummy_handler)
+            .build())?;
+        scheduler.run_pending_at(at("2030-01-01T20:10:00Z"))?;
+        assert_eq!(scheduler.jobs[0].last_run, None);
+        assert_eq!(scheduler.jobs[0].next_run, Some(at("2030-01-02T09:00:00Z")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cron_every_nth() -> Result<(), JobSchedulerError> {
+        let job = JobBuilder::new("every-third-second")
+            .cron_every_nth("* * * * * * *", 3)
+            .add_handler(dummy_handler)
+            .build();
+        let first = job.next_run.unwrap();
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(job)?;
+
+        let expected: Vec<SystemTime> = (1..=3).map(|k| first + Duration::from_secs(3 * k)).collect();
+        assert_eq!(scheduler.next_runs(4).into_iter().map(|(_, time)| time).collect::<Vec<_>>()[1..], expected);
+        let mut fired = Vec::new();
+        let mut now = first;
+        for _ in 0..3 {
+            scheduler.run_pending_at(now)?;
+            now = scheduler.jobs[0].next_run.unwrap();
+            fired.push(now);
+        }
+        assert_eq!(fired, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_pending_parallel() -> Result<(), JobSchedulerError> {
+        use std::sync::{Arc, Barrier};
+        // Each handler waits for the others, so the sweep only finishes if they run together
+        let barrier = Arc::new(Barrier::new(3));
+        let mut scheduler = Scheduler::new();
+        for name in ["a", "b", "c"] {
+            let barrier = barrier.clone();
+            scheduler.add_job(JobBuilder::new(name)
+                .recurring(RecurringInterval::Hourly(1), Some(ScheduleTime::Delay(Duration::from_secs(60))))
+                .add_handler(move || {
+                    barrier.wait();
+                })
+                .build())?;
+        }
+        let now = scheduler.jobs[0].next_run.unwrap() + Duration::from_secs(1);
+        scheduler.run_pending_parallel_at(now)?;
+        for job in &scheduler.jobs {
+            assert_eq!(job.schedules[0].run_count, 1);
+            assert!(job.next_run.unwrap() > now);
+            assert_eq!(job.last_run, Some(now));
+        }
+
+        // Unhandled failures are combined once every job has been settled
+        let mut scheduler = Scheduler::new();
+        for name in ["ok", "bad-1", "bad-2"] {
+            let fails = name.starts_with("bad");
+            scheduler.add_job(JobBuilder::new(name)
+                .once(ScheduleTime::Delay(Duration::from_secs(60)))
+                .add_handler(move || assert!(!fails, "handler failed"))
+                .build())?;
+        }
+        let now = SystemTime::now() + Duration::from_secs(120);
+        match scheduler.run_pending_parallel_at(now) {
+            Err(JobSchedulerError::ExecutionFailed(msg)) => assert!(msg.starts_with("2 jobs failed")),
+            other => panic!("expected ExecutionFailed, got {:?}", other),
+        }
+        assert_eq!(scheduler.jobs[0].state(), JobState::Completed);
+        // The failed jobs stay due
+        assert!(scheduler.jobs[1..].iter().all(|job| job.next_run.is_some()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_idempotency_key_across_schedulers() -> Result<(), JobSchedulerError> {
+        use std::sync::{Arc, Mutex};
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        // A store shared by schedulers standing in for separate processes
+        #[derive(Clone, Default)]
+        struct SharedStore(Arc<Mutex<HashSet<String>>>);
+        impl DedupStore for SharedStore {
+            fn claim(&mut self, key: &str) -> bool {
+                self.0.lock().unwrap().insert(key.to_string())
+            }
+        }
+
+        let store = SharedStore::default();
+        let runs = Arc::new(AtomicU32::new(0));
+        let start = SystemTime::now() + Duration::from_secs(60);
+        let mut schedulers: Vec<Scheduler> = (0..2).map(|_| Scheduler::new().dedup_store(store.clone())).collect();
+        for scheduler in schedulers.iter_mut() {
+            let runs = runs.clone();
+            scheduler.add_job(JobBuilder::new("report")
+                .recurring(RecurringInterval::Hourly(1), Some(ScheduleTime::At(start)))
+                .idempotency_key(|scheduled| {
+                    let secs = scheduled.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+                    format!("report-{}", secs / 3600)
+                })
+                .add_handler(move || {
+                    runs.fetch_add(1, Ordering::SeqCst);
+                })
+                .build())?;
+        }
+        for scheduler in schedulers.iter_mut() {
+            scheduler.run_pending_at(start)?;
+        }
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(schedulers[1].jobs[0].recent_runs()[0].outcome, RunOutcome::Skipped);
+
+        // The next hour is a new key
+        let later = start + Duration::from_secs(3600);
+        for scheduler in schedulers.iter_mut() {
+            scheduler.run_pending_at(later)?;
+        }
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_jobs_in_window() -> Result<(), JobSchedulerError> {
+        let now = SystemTime::now();
+        let mut scheduler = Scheduler::new();
+        for (name, delay) in [("soon", 60), ("later", 7200), ("overdue", 1)] {
+            scheduler.add_job(JobBuilder::new(name)
+                .once(ScheduleTime::At(now + Duration::from_secs(delay)))
+                .add_handler(dummy_handler)
+                .build())?;
+        }
+        let now = now + Duration::from_secs(10);
+        let names: Vec<_> = scheduler.jobs_in_window_at(now, Duration::from_secs(3600))
+            .map(|job| job.name.as_deref().unwrap())
+            .collect();
+        assert_eq!(names, ["soon"]);
+        assert_eq!(scheduler.jobs_in_window_at(now, Duration::from_secs(86400)).count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_min_interval() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new().min_interval(Duration::from_secs(1));
+        let too_fast = JobBuilder::new("spin")
+            .recurring(RecurringInterval::Secondly(0), None)
+            .add_handler(dummy_handler)
+            .build();
+        assert!(matches!(scheduler.add_job(too_fast), Err(JobSchedulerError::InvalidSchedule(_))));
+        scheduler.add_job(JobBuilder::new("tick")
+            .recurring(RecurringInterval::Secondly(1), None)
+            .add_handler(dummy_handler)
+            .build())?;
+        assert_eq!(scheduler.jobs.len(), 1);
+
+        // Merging cannot get around the floor
+        let mut other = Scheduler::new();
+        other.add_job(JobBuilder::new("spin")
+            .recurring(RecurringInterval::Secondly(0), None)
+            .add_handler(dummy_handler)
+            .build())?;
+        assert!(matches!(scheduler.merge(other), Err(JobSchedulerError::InvalidSchedule(_))));
+        assert_eq!(scheduler.jobs.len(), 1);
+
+        // Nor can templates, and none of their jobs are added
+        let template = JobTemplate::from(JobBuilder::new_unnamed().recurring(RecurringInterval::Secondly(0), None));
+        let added = scheduler.add_from_template(&template, &["a", "b", "c"], |_| dummy_handler);
+        assert!(matches!(added, Err(JobSchedulerError::InvalidSchedule(_))));
+        assert_eq!(scheduler.jobs.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejected_job_from_handle_keeps_loop_running() -> Result<(), JobSchedulerError> {
+        use std::sync::{Arc, Mutex};
+
+        let rejected = Arc::new(Mutex::new(Vec::new()));
+        let errors = Arc::clone(&rejected);
+        let mut scheduler = Scheduler::new().min_interval(Duration::from_secs(10));
+        scheduler.on_any_error(move |id, err| errors.lock().unwrap().push((id, err.to_string())));
+        let ran = Arc::new(Mutex::new(false));
+        let flag = Arc::clone(&ran);
+        scheduler.add_job(JobBuilder::new("valid")
+            .once(ScheduleTime::Delay(Duration::from_millis(50)))
+            .add_handler(move || *flag.lock().unwrap() = true)
+            .build())?;
+        let too_fast = JobBuilder::new("too_fast")
+            .recurring(RecurringInterval::Secondly(1), None)
+            .add_handler(dummy_handler)
+            .build();
+        let too_fast_id = too_fast.id;
+        scheduler.handle().add_job(too_fast)?;
+
+        scheduler.run_non_blocking()?;
+        assert!(*ran.lock().unwrap());
+        assert_eq!(scheduler.jobs.len(), 1);
+        let rejected = rejected.lock().unwrap();
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].0, too_fast_id);
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_all() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(JobBuilder::new("fine")
+            .recurring(RecurringInterval::Hourly(1), None)
+            .add_handler(dummy_handler)
+            .build())?;
+        assert!(scheduler.validate_all().is_ok());
+
+        let unbound = JobBuilder::new("unbound").recurring(RecurringInterval::Hourly(1), None).build();
+        let unbound_id = unbound.id;
+        scheduler.add_unbound_job(unbound)?;
+        let done = JobBuilder::new("done")
+            .once(ScheduleTime::Delay(Duration::from_secs(60)))
+            .add_handler(dummy_handler)
+            .build();
+        let done_id = done.id;
+        scheduler.add_job(done)?;
+        scheduler.run_pending_at(SystemTime::now() + Duration::from_secs(120))?;
+
+        let problems = scheduler.validate_all().unwrap_err();
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].0, unbound_id);
+        assert!(matches!(problems[0].1, JobSchedulerError::HandlerNotBuilt));
+        assert_eq!(problems[1].0, done_id);
+        assert!(matches!(problems[1].1, JobSchedulerError::InvalidSchedule(_)));
+
+        // A dependency that was never added is reported
+        let mut scheduler = Scheduler::new();
+        let upstream = JobBuilder::new("upstream")
+            .recurring(RecurringInterval::Hourly(1), None)
+            .add_handler(dummy_handler)
+            .build();
+        let upstream_id = upstream.id;
+        let downstream = JobBuilder::new("downstream")
+            .recurring(RecurringInterval::Hourly(1), None)
+            .after(upstream_id)
+            .add_handler(dummy_handler)
+            .build();
+        let downstream_id = downstream.id;
+        scheduler.add_job(downstream)?;
+        let problems = scheduler.validate_all().unwrap_err();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].0, downstream_id);
+        assert!(matches!(&problems[0].1, JobSchedulerError::InvalidSchedule(msg) if msg.contains(&upstream_id.to_string())));
+        scheduler.add_job(upstream)?;
+        assert!(scheduler.validate_all().is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_on_start() -> Result<(), JobSchedulerError> {
+        let start = SystemTime::now();
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(JobBuilder::new("warm_cache")
+            .recurring(RecurringInterval::Hourly(1), None)
+            .run_on_start()
+            .add_handler(dummy_handler)
+            .build())?;
+        // Fires on the first sweep, well before the first hourly run
+        scheduler.run_pending_at(start)?;
+        let job = &scheduler.jobs[0];
+        assert_eq!(job.last_run, Some(start));
+        assert_eq!(job.schedules[0].run_count, 0);
+        let first_hourly = job.next_run.unwrap();
+        assert!(first_hourly > start + Duration::from_secs(3500));
+
+        // Then follows the normal cadence
+        scheduler.run_pending_at(start + Duration::from_secs(60))?;
+        assert_eq!(scheduler.jobs[0].total_run_count(), 1);
+        scheduler.run_pending_at(first_hourly)?;
+        let job = &scheduler.jobs[0];
+        assert_eq!(job.schedules[0].run_count, 1);
+        assert_eq!(job.next_run, Some(first_hourly + Duration::from_secs(3600)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_max_runs_resumes_exhausted_job() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        let job = JobBuilder::new("capped")
+            .recurring(RecurringInterval::Hourly(1), Some(ScheduleTime::Delay(Duration::from_secs(60))))
+            .max_repeat(1)
+            .add_handler(dummy_handler)
+            .build();
+        let id = job.id;
+        let first = job.next_run.unwrap();
+        scheduler.add_job(job)?;
+        scheduler.run_pending_at(first)?;
+        assert_eq!(scheduler.jobs[0].state(), JobState::Completed);
+
+        scheduler.set_max_runs(id, Some(2))?;
+        assert_eq!(scheduler.jobs[0].state(), JobState::Active);
+        assert_eq!(scheduler.jobs[0].next_run, Some(first + Duration::from_secs(3600)));
+        scheduler.run_pending_at(first + Duration::from_secs(3600))?;
+        assert_eq!(scheduler.jobs[0].total_run_count(), 2);
+        assert_eq!(scheduler.jobs[0].state(), JobState::Completed);
+
+        assert!(matches!(scheduler.set_max_runs(Uuid::new_v4(), None), Err(JobSchedulerError::JobNotFound(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_coalesce_missed_runs() -> Result<(), JobSchedulerError> {
+        let start = SystemTime::now() + Duration::from_secs(60);
+        let mut scheduler = Scheduler::new();
+        for (name, coalesce) in [("catch_up", false), ("coalesced", true)] {
+            let mut builder = JobBuilder::new(name)
+                .recurring(RecurringInterval::Hourly(1), Some(ScheduleTime::At(start)))
+                .add_handler(dummy_handler);
+            if coalesce {
+                builder = builder.coalesce();
+            }
+            scheduler.add_job(builder.build())?;
+        }
+        // Four occurrences have been missed by the time of the sweep
+        let now = start + Duration::from_secs(3 * 3600 + 1800);
+        scheduler.run_pending_at(now)?;
+        scheduler.run_pending_at(now)?;
+        let catch_up = &scheduler.jobs[0];
+        assert_eq!(catch_up.total_run_count(), 2);
+        assert_eq!(catch_up.next_run, Some(start + Duration::from_secs(2 * 3600)));
+        let coalesced = &scheduler.jobs[1];
+        assert_eq!(coalesced.total_run_count(), 1);
+        assert_eq!(coalesced.next_run, Some(start + Duration::from_secs(4 * 3600)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_idempotency_key_refires_failed_run() -> Result<(), JobSchedulerError> {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&calls);
+        let start = SystemTime::now() + Duration::from_secs(60);
+        let mut scheduler = Scheduler::new().dedup_store(HashSet::new());
+        scheduler.add_job(JobBuilder::new("flaky")
+            .once(ScheduleTime::At(start))
+            .idempotency_key(|_| "flaky".to_string())
+            .add_handler(move || {
+                assert!(counter.fetch_add(1, Ordering::SeqCst) > 0, "first run fails");
+            })
+            .build())?;
+        // Without an error handler the failed run stays due and fires again under its own key
+        assert!(scheduler.run_pending_at(start).is_err());
+        scheduler.run_pending_at(start)?;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(scheduler.jobs[0].recent_runs().last().unwrap().outcome, RunOutcome::Succeeded);
+        assert_eq!(scheduler.jobs[0].state(), JobState::Completed);
+        Ok(())
+    }
+
+    #[test]
+    fn test_drift_ignores_immediate_runs() -> Result<(), JobSchedulerError> {
+        let start = SystemTime::now();
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(JobBuilder::new("warm_cache")
+            .recurring(RecurringInterval::Hourly(1), None)
+            .run_on_start()
+            .add_handler(dummy_handler)
+            .build())?;
+        scheduler.run_pending_at(start)?;
+        assert_eq!(scheduler.jobs[0].stats(), JobStats::default());
+
+        // The hourly run is measured against its own time
+        let hourly = scheduler.jobs[0].next_run.unwrap();
+        scheduler.run_pending_at(hourly + Duration::from_secs(2))?;
+        let stats = scheduler.jobs[0].stats();
+        assert!(stats.last_drift.unwrap() >= Duration::from_secs(2));
+        assert!(stats.max_drift.unwrap() < Duration::from_secs(3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_drift_after_slow_job() -> Result<(), JobSchedulerError> {
+        let start = SystemTime::now() + Duration::from_secs(60);
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(JobBuilder::new("slow")
+            .once(ScheduleTime::At(start))
+            .add_handler(|| sleep(Duration::from_millis(50)))
+            .build())?;
+        scheduler.add_job(JobBuilder::new("next")
+            .once(ScheduleTime::At(start))
+            .add_handler(dummy_handler)
+            .build())?;
+        scheduler.run_pending_at(start)?;
+        let slow = scheduler.jobs[0].stats();
+        let next = scheduler.jobs[1].stats();
+        assert!(slow.last_drift.unwrap() < Duration::from_millis(50));
+        assert!(next.last_drift.unwrap() >= Duration::from_millis(50));
+        assert_eq!(next.max_drift, next.last_drift);
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_pending_continues_past_failures() -> Result<(), JobSchedulerError> {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let ran = Arc::new(AtomicU32::new(0));
+        let ran_clone = ran.clone();
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(JobBuilder::new("bad")
+            .once(ScheduleTime::Delay(Duration::from_secs(60)))
+            .add_handler(|| panic!("handler failed"))
+            .build())?;
+        scheduler.add_job(JobBuilder::new("good")
+            .once(ScheduleTime::Delay(Duration::from_secs(60)))
+            .add_handler(move || {
+                ran_clone.fetch_add(1, Ordering::SeqCst);
+            })
+            .build())?;
+        let now = SystemTime::now() + Duration::from_secs(120);
+        assert!(matches!(scheduler.run_pending_at(now), Err(JobSchedulerError::ExecutionFailed(_))));
+        // The later job still ran, while the failed one stays due
+        assert_eq!(ran.load(Ordering::SeqCst), 1);
+        assert_eq!(scheduler.jobs[1].state(), JobState::Completed);
+        assert!(scheduler.jobs[0].next_run.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_next_run_human() -> Result<(), JobSchedulerError> {
         let mut scheduler = Scheduler::new();
-        
-        // Create a job that will run immediately
-        let job = JobBuilder::new("immediate")
-            .once(ScheduleTime::At(SystemTime::now()))
+        assert_eq!(scheduler.next_run_human(), None);
+        scheduler.add_job(JobBuilder::new("soon")
+            .once(ScheduleTime::Delay(Duration::from_secs(303)))
             .add_handler(dummy_handler)
-            .build();
-            
-        scheduler.add_job(job)?;
-        
-        // Should be one job before running
-        assert_eq!(scheduler.jobs.len(), 1);
-        
-        // Run pending jobs
-        scheduler.run_pending()?;
-        
-        // Should still have one job but with updated last_run time and null next_run
-        assert_eq!(scheduler.jobs.len(), 1);
-        assert!(scheduler.jobs[0].last_run.is_some());
-        assert!(scheduler.jobs[0].next_run.is_none()); // Should be None after running once
-        
+            .build())?;
+        let next = scheduler.next_run().unwrap();
+        let now = next - Duration::from_millis(303_400);
+        assert_eq!(scheduler.next_run_human_at(now).as_deref(), Some("5m 3s"));
+        // Overdue runs are due now
+        assert_eq!(scheduler.next_run_human_at(next + Duration::from_secs(1)).as_deref(), Some("0s"));
         Ok(())
     }
-    
+
     #[test]
-    fn test_run_recurring_jobs() -> Result<(), JobSchedulerError> {
+    fn test_now_once() -> Result<(), JobSchedulerError> {
+        let built = SystemTime::now();
         let mut scheduler = Scheduler::new();
-        
-        // Create a job that recurs every second
-        let recur_time = SystemTime::now();
-        
-        let job = JobBuilder::new("recurring")
-            .recurring(RecurringInterval::Secondly(1), Some(ScheduleTime::At(recur_time)))
+        scheduler.add_job(JobBuilder::new("asap")
+            .now_once()
             .add_handler(dummy_handler)
-            .build();
-            
-        scheduler.add_job(job)?;
-        
-        // Initial state check
-        assert_eq!(scheduler.jobs.len(), 1);
-        assert!(scheduler.jobs[0].next_run.is_some());
-        
-        // Run pending jobs
-        scheduler.run_pending()?;
-        
-        // Should have updated last_run and scheduled next run
-        let last_run = scheduler.jobs[0].last_run;
-        assert!(last_run.is_some());
-        
-        let next_run = scheduler.jobs[0].next_run;
-        assert!(next_run.is_some());
-        
-        // Next run should be approximately one second after the initial time
-        let expected_next = recur_time + Duration::from_secs(1);
-        let diff = if expected_next > next_run.unwrap() {
-            expected_next.duration_since(next_run.unwrap())
-        } else {
-            next_run.unwrap().duration_since(expected_next)
-        };
-        
-        assert!(diff.unwrap_or_default() < Duration::from_millis(100));
-        
+            .build())?;
+        // Reports when it was built rather than a placeholder time
+        assert!(scheduler.jobs[0].next_run.is_some_and(|next| next >= built));
+        // Fires even when the sweep's clock reads earlier than the build time
+        let skewed = SystemTime::now() - Duration::from_secs(5);
+        scheduler.run_pending_at(skewed)?;
+        let job = &scheduler.jobs[0];
+        assert_eq!(job.last_run, Some(skewed));
+        assert_eq!(job.schedules[0].run_count, 1);
+        assert_eq!(job.next_run, None);
+        assert_eq!(job.state(), JobState::Completed);
+        assert_eq!(job.schedules[0].schedule_type.to_string(), "once, on the next sweep");
         Ok(())
     }
-    
+
     #[test]
-    fn test_run_job_with_max_runs() -> Result<(), JobSchedulerError> {
+    fn test_list_jobs_sorted_by() -> Result<(), JobSchedulerError> {
         let mut scheduler = Scheduler::new();
-        
-        // Create a recurring job with max 2 runs
-        let recur_time = SystemTime::now();
-        
-        let job = JobBuilder::new("limited-runs")
-            .recurring(RecurringInterval::Secondly(1), Some(ScheduleTime::At(recur_time)))
-            .max_repeat(2)
+        for (name, delay, priority) in [("charlie", 30, 0), ("alpha", 10, -1), ("bravo", 20, 5)] {
+            scheduler.add_job(JobBuilder::new(name)
+                .recurring(RecurringInterval::Hourly(1), Some(ScheduleTime::Delay(Duration::from_secs(delay))))
+                .priority(priority)
+                .add_handler(dummy_handler)
+                .build())?;
+        }
+        scheduler.add_job(JobBuilder::new_unnamed()
+            .once(ScheduleTime::Delay(Duration::from_secs(86400)))
             .add_handler(dummy_handler)
-            .build();
-            
-        scheduler.add_job(job)?;
-        
-        // Run first execution
-        scheduler.run_pending()?;
-        assert!(scheduler.jobs[0].next_run.is_some());
-        
-        // Wait a bit to ensure the next schedule is ready
-        sleep(Duration::from_secs(1));
-        
-        // Run second execution
-        scheduler.run_pending()?;
-        
-        // Wait a bit more
-        sleep(Duration::from_secs(1));
-        
-        // Run again, but there should be no next run since we hit max_runs=2
-        scheduler.run_pending()?;
-        assert!(scheduler.jobs[0].next_run.is_none());
-        
+            .build())?;
+        fn names(scheduler: &Scheduler, key: SortKey) -> Vec<Option<String>> {
+            scheduler.list_jobs_sorted_by(key).into_iter().map(|job| job.name.clone()).collect()
+        }
+        let some = |name: &str| Some(name.to_string());
+
+        assert_eq!(names(&scheduler, SortKey::NextRun), vec![some("alpha"), some("bravo"), some("charlie"), None]);
+        assert_eq!(names(&scheduler, SortKey::Name), vec![some("alpha"), some("bravo"), some("charlie"), None]);
+        // Never-run jobs tie, keeping the order they were added in
+        assert_eq!(names(&scheduler, SortKey::LastRun), vec![some("charlie"), some("alpha"), some("bravo"), None]);
+        // The unnamed job ties with charlie at the default priority
+        assert_eq!(names(&scheduler, SortKey::Priority), vec![some("bravo"), some("charlie"), None, some("alpha")]);
+
+        // charlie runs twice, bravo once
+        let start = scheduler.jobs[0].next_run.unwrap();
+        scheduler.jobs[1].paused = true;
+        scheduler.run_pending_at(start)?;
+        scheduler.jobs[2].paused = true;
+        scheduler.run_pending_at(start + Duration::from_secs(3600))?;
+        assert_eq!(names(&scheduler, SortKey::RunCount), vec![some("alpha"), None, some("bravo"), some("charlie")]);
+        assert_eq!(names(&scheduler, SortKey::LastRun), vec![some("bravo"), some("charlie"), some("alpha"), None]);
+        assert_eq!(names(&scheduler, SortKey::NextRun), scheduler.list_all_jobs().into_iter().map(|job| job.name.clone()).collect::<Vec<_>>());
         Ok(())
     }
-    
+
     #[test]
-    fn test_list_all_jobs() -> Result<(), JobSchedulerError> {
+    fn test_opportunistic() -> Result<(), JobSchedulerError> {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering};
+        let quiet = Arc::new(AtomicBool::new(false));
+        let deadline = SystemTime::now() + Duration::from_secs(3600);
         let mut scheduler = Scheduler::new();
-        
-        let job1 = JobBuilder::new("job1")
-            .once(ScheduleTime::At(SystemTime::now() + Duration::from_secs(60)))
+        let check = quiet.clone();
+        scheduler.add_job(JobBuilder::new("when-quiet")
+            .opportunistic(deadline, move || check.load(Ordering::SeqCst))
             .add_handler(dummy_handler)
-            .build();
-            
-        let job2 = JobBuilder::new("job2")
-            .once(ScheduleTime::At(SystemTime::now() + Duration::from_secs(30)))
+            .build())?;
+        scheduler.add_job(JobBuilder::new("never-quiet")
+            .opportunistic(deadline, || false)
             .add_handler(dummy_handler)
-            .build();
-            
-        let job3 = JobBuilder::new("job3")
-            .once(ScheduleTime::At(SystemTime::now() + Duration::from_secs(90)))
+            .build())?;
+
+        // Busy: both wait and are asked again after the poll interval
+        let start = SystemTime::now();
+        scheduler.run_pending_at(start)?;
+        assert!(scheduler.jobs.iter().all(|job| job.last_run.is_none()));
+        assert_eq!(scheduler.next_run(), Some(start + DEFAULT_MAX_POLL_INTERVAL));
+
+        // The first job runs as soon as load allows
+        quiet.store(true, Ordering::SeqCst);
+        let later = start + DEFAULT_MAX_POLL_INTERVAL;
+        scheduler.run_pending_at(later)?;
+        assert_eq!(scheduler.jobs[0].last_run, Some(later));
+        assert_eq!(scheduler.jobs[0].state(), JobState::Completed);
+        assert_eq!(scheduler.jobs[1].last_run, None);
+
+        // The second is forced once its deadline arrives
+        scheduler.jobs[1].schedules[0].next_run = Some(deadline);
+        scheduler.jobs[1].refresh_next_run();
+        scheduler.run_pending_at(deadline - Duration::from_secs(1))?;
+        assert_eq!(scheduler.jobs[1].last_run, None);
+        assert_eq!(scheduler.jobs[1].next_run, Some(deadline));
+        scheduler.run_pending_at(deadline)?;
+        assert_eq!(scheduler.jobs[1].last_run, Some(deadline));
+        assert_eq!(scheduler.jobs[1].state(), JobState::Completed);
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_jobs() -> Result<(), JobSchedulerError> {
+        let job = |name: &str| JobBuilder::new(name)
+            .once(ScheduleTime::Delay(Duration::from_secs(60)))
             .add_handler(dummy_handler)
             .build();
-            
-        scheduler.add_job(job1)?;
-        scheduler.add_job(job2)?;
-        scheduler.add_job(job3)?;
-        
-        let all_jobs = scheduler.list_all_jobs();
-        assert_eq!(all_jobs.len(), 3);
-        // Jobs sorted by next_run: time2 (30s), time1 (60s), time3 (90s)
-        assert_eq!(all_jobs[0].name, Some("job2".to_string()));
-        assert_eq!(all_jobs[1].name, Some("job1".to_string()));
-        assert_eq!(all_jobs[2].name, Some("job3".to_string()));
+        let mut scheduler = Scheduler::new();
+        let first = job("first");
+        let first_id = first.id;
+        let ids = scheduler.add_jobs(vec![first, job("second")])?;
+        assert_eq!(ids[0], first_id);
+        assert_eq!(scheduler.jobs.iter().map(|job| job.id).collect::<Vec<_>>(), ids);
 
+        // One bad job keeps the whole batch out
+        let unscheduled = JobBuilder::new("unscheduled").add_handler(dummy_handler).build();
+        assert!(matches!(scheduler.add_jobs(vec![job("third"), unscheduled]), Err(JobSchedulerError::MissingSchedule)));
+        let again = JobBuilder::new("again").id(first_id).once(ScheduleTime::Delay(Duration::from_secs(60))).add_handler(dummy_handler).build();
+        assert!(scheduler.add_jobs(vec![job("third"), again]).is_err());
+        let repeated = job("repeated");
+        let twin = JobBuilder::new("twin").id(repeated.id).once(ScheduleTime::Delay(Duration::from_secs(60))).add_handler(dummy_handler).build();
+        assert!(scheduler.add_jobs(vec![repeated, twin]).is_err());
+        assert_eq!(scheduler.jobs.len(), 2);
         Ok(())
     }
-    
+
     #[test]
-    fn test_compute_next_run_recurring_intervals() {
-        // Test different recurring interval calculations
-        let now = SystemTime::now();
-        
-        // Test secondly
-        let mut secondly_sched = Schedule {
-            schedule_type: ScheduleType::Recurring(RecurringSchedule {
-                interval: RecurringInterval::Secondly(5),
-                next_run: now,
-            }),
-            max_runs: None,
-            run_count: 0,
-        };
-        let next_secondly = Scheduler::compute_next_run(&mut secondly_sched).unwrap();
-        assert_eq!(next_secondly, now + Duration::from_secs(5));
-        
-        // Test hourly
-        let mut hourly_sched = Schedule {
-            schedule_type: ScheduleType::Recurring(RecurringSchedule {
-                interval: RecurringInterval::Hourly(2),
-                next_run: now,
-            }),
-            max_runs: None,
-            run_count: 0,
-        };
-        let next_hourly = Scheduler::compute_next_run(&mut hourly_sched).unwrap();
-        assert_eq!(next_hourly, now + Duration::from_secs(2 * 3600));
-        
-        // Test daily
-        let mut daily_sched = Schedule {
-            schedule_type: ScheduleType::Recurring(RecurringSchedule {
-                interval: RecurringInterval::Daily(1),
-                next_run: now,
-            }),
-            max_runs: None,
-            run_count: 0,
-        };
-        let next_daily = Scheduler::compute_next_run(&mut daily_sched).unwrap();
-        assert_eq!(next_daily, now + Duration::from_secs(86400));
-        
-        // Test custom expression
-        let mut custom_sched = Schedule {
-            schedule_type: ScheduleType::Recurring(RecurringSchedule {
-                interval: RecurringInterval::Custom { 
-                    expression: "weekly".to_string(), 
-                    frequency: 1 
-                },
-                next_run: now,
-            }),
-            max_runs: None,
-            run_count: 0,
+    fn test_time_resolution() -> Result<(), JobSchedulerError> {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(2_000_000_000);
+        let jobs = || {
+            [Duration::ZERO, Duration::from_micros(1)].map(|after| JobBuilder::new("close")
+                .once(ScheduleTime::At(start + after))
+                .add_handler(dummy_handler)
+                .build())
         };
-        let next_custom = Scheduler::compute_next_run(&mut custom_sched).unwrap();
-        assert_eq!(next_custom, now + Duration::from_secs(7 * 86400));
+
+        // Compared exactly, a sweep at the first run misses the second by a microsecond
+        let mut scheduler = Scheduler::new();
+        scheduler.add_jobs(jobs().into())?;
+        scheduler.run_pending_at(start)?;
+        assert_eq!(scheduler.jobs[0].last_run, Some(start));
+        assert_eq!(scheduler.jobs[1].last_run, None);
+
+        // With millisecond resolution both fire in the same sweep
+        let mut scheduler = Scheduler::new().time_resolution(Duration::from_millis(1));
+        scheduler.add_jobs(jobs().into())?;
+        scheduler.run_pending_at(start)?;
+        assert!(scheduler.jobs.iter().all(|job| job.last_run == Some(start)));
+        assert!(scheduler.jobs.iter().all(|job| job.state() == JobState::Completed));
+        Ok(())
     }
-    
+
     #[test]
-    fn test_max_runs_limit() {
-        let now = SystemTime::now();
-        let mut sched = Schedule {
-            schedule_type: ScheduleType::Recurring(RecurringSchedule {
-                interval: RecurringInterval::Secondly(1),
-                next_run: now,
-            }),
-            max_runs: Some(3),
-            run_count: 3,  // Already reached max_runs
-        };
-        
-        let next_run = Scheduler::compute_next_run(&mut sched);
-        assert!(next_run.is_none());
+    fn test_set_job_timezone() -> Result<(), JobSchedulerError> {
+        use chrono::{DateTime, Timelike};
+        let mut scheduler = Scheduler::new();
+        let job = JobBuilder::new("morning-report")
+            .cron_tz("0 0 9 * * * *", Tz::UTC)
+            .add_handler(dummy_handler)
+            .build();
+        let id = job.id;
+        scheduler.add_job(job)?;
+        let before = scheduler.jobs[0].next_run.unwrap();
+        assert_eq!(DateTime::<Utc>::from(before).hour(), 9);
+
+        scheduler.set_job_timezone(id, Tz::Asia__Tokyo)?;
+        let after = scheduler.jobs[0].next_run.unwrap();
+        assert_ne!(after, before);
+        // 09:00 in Tokyo is midnight UTC
+        assert_eq!(DateTime::<Utc>::from(after).with_timezone(&Tz::Asia__Tokyo).hour(), 9);
+        assert_eq!(DateTime::<Utc>::from(after).hour(), 0);
+        assert!(after > SystemTime::now());
+
+        assert!(matches!(scheduler.set_job_timezone(Uuid::new_v4(), Tz::UTC), Err(JobSchedulerError::JobNotFound(_))));
+        let plain = JobBuilder::new("plain").cron("0 0 9 * * * *").add_handler(dummy_handler).build();
+        let plain_id = plain.id;
+        scheduler.add_job(plain)?;
+        assert!(matches!(scheduler.set_job_timezone(plain_id, Tz::UTC), Err(JobSchedulerError::InvalidSchedule(_))));
+        Ok(())
     }
-    
+
     #[test]
-    fn test_peek_next_run() {
-        let now = SystemTime::now();
-        
-        // Test recurring schedule
-        let recurring_sched = Schedule {
-            schedule_type: ScheduleType::Recurring(RecurringSchedule {
-                interval: RecurringInterval::Secondly(1),
-                next_run: now + Duration::from_secs(5),
-            }),
-            max_runs: None,
-            run_count: 0,
-        };
-        
-        let peeked = Scheduler::peek_next_run(&recurring_sched);
-        assert_eq!(peeked.unwrap(), now + Duration::from_secs(5));
-        
-        // Test once schedule
-        let once_sched = Schedule {
-            schedule_type: ScheduleType::Once(now),
-            max_runs: Some(1),
-            run_count: 0,
-        };
-        
-        let peeked_once = Scheduler::peek_next_run(&once_sched);
-        assert!(peeked_once.is_none());
+    fn test_take_results() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        let job = JobBuilder::new("import")
+            .recurring(RecurringInterval::Minutely(1), None)
+            .add_handler_returning(|| 42usize)
+            .build();
+        let id = job.id;
+        scheduler.add_job(job)?;
+        assert!(scheduler.take_results(id)?.is_empty());
+
+        let first = scheduler.jobs[0].next_run.unwrap();
+        scheduler.run_pending_at(first)?;
+        scheduler.run_pending_at(first + Duration::from_secs(60))?;
+        let results = scheduler.take_results(id)?;
+        let rows: Vec<usize> = results.into_iter().map(|value| *value.downcast::<usize>().unwrap()).collect();
+        assert_eq!(rows, vec![42, 42]);
+        // Taking drains the queue
+        assert!(scheduler.take_results(id)?.is_empty());
+        assert!(matches!(scheduler.take_results(Uuid::new_v4()), Err(JobSchedulerError::JobNotFound(_))));
+        Ok(())
     }
-    
+
     #[test]
-    fn test_cron_schedule() -> Result<(), JobSchedulerError> {
+    fn test_dynamic_schedule() -> Result<(), JobSchedulerError> {
+        use crate::scheduler::types::DynamicSchedule;
+        // Runs on whole multiples of 90 seconds since the epoch
+        struct EveryNinety;
+        impl DynamicSchedule for EveryNinety {
+            fn next_after(&self, after: SystemTime) -> Option<SystemTime> {
+                let secs = after.duration_since(SystemTime::UNIX_EPOCH).ok()?.as_secs();
+                Some(SystemTime::UNIX_EPOCH + Duration::from_secs((secs / 90 + 1) * 90))
+            }
+            fn describe(&self) -> String {
+                "every 90s on the epoch".to_string()
+            }
+        }
+
         let mut scheduler = Scheduler::new();
-        let cron_str = "0 0 * * * *"; // Run at midnight every day
-        
-        let job = JobBuilder::new("cron-job")
-            .cron(cron_str)
+        scheduler.add_job(JobBuilder::new("dynamic")
+            .dynamic(EveryNinety)
             .add_handler(dummy_handler)
+            .build())?;
+        let first = scheduler.jobs[0].next_run.unwrap();
+        assert_eq!(first.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs() % 90, 0);
+        assert_eq!(scheduler.jobs[0].schedules[0].schedule_type.to_string(), "every 90s on the epoch");
+        let projected: Vec<SystemTime> = scheduler.next_runs(3).into_iter().map(|(_, time)| time).collect();
+        assert_eq!(projected, vec![first, first + Duration::from_secs(90), first + Duration::from_secs(180)]);
+
+        scheduler.run_pending_at(first)?;
+        assert_eq!(scheduler.jobs[0].last_run, Some(first));
+        assert_eq!(scheduler.jobs[0].next_run, Some(first + Duration::from_secs(90)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_by_type() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        assert!(scheduler.count_by_type().is_empty());
+        scheduler.add_jobs(vec![
+            JobBuilder::new("cron").cron("0 0 * * * * *").add_handler(dummy_handler).build(),
+            JobBuilder::new("mixed")
+                .cron("0 30 * * * * *")
+                .cron("0 45 * * * * *")
+                .recurring(RecurringInterval::Hourly(1), None)
+                .add_handler(dummy_handler)
+                .build(),
+            JobBuilder::new("once").once(ScheduleTime::Delay(Duration::from_secs(60))).add_handler(dummy_handler).build(),
+        ])?;
+        let counts = scheduler.count_by_type();
+        assert_eq!(counts.len(), 3);
+        // Two cron schedules on one job count it once
+        assert_eq!(counts["cron"], 2);
+        assert_eq!(counts["recurring"], 1);
+        assert_eq!(counts["once"], 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_unbound_job() -> Result<(), JobSchedulerError> {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicU32, Ordering};
+        let mut scheduler = Scheduler::new();
+        let staged = JobBuilder::new("staged")
+            .recurring(RecurringInterval::Minutely(1), None)
             .build();
-            
-        scheduler.add_job(job)?;
-        
-        assert!(scheduler.next_run().is_some());
-        
+        let id = staged.id;
+        // `add_job` still insists on a handler
+        assert!(matches!(scheduler.add_job(JobBuilder::new("unbound").once(ScheduleTime::Delay(Duration::from_secs(60))).build()), Err(JobSchedulerError::HandlerNotBuilt)));
+        scheduler.add_unbound_job(staged)?;
+        assert!(matches!(scheduler.add_unbound_job(JobBuilder::new("unscheduled").build()), Err(JobSchedulerError::MissingSchedule)));
+
+        // Staged jobs are skipped without error and not waited for
+        let first = scheduler.jobs[0].next_run.unwrap();
+        scheduler.run_pending_at(first)?;
+        assert_eq!(scheduler.jobs[0].last_run, None);
+        assert_eq!(scheduler.jobs[0].next_run, Some(first));
+        assert_eq!(scheduler.next_run(), None);
+
+        // Once bound, the job fires
+        let runs = Arc::new(AtomicU32::new(0));
+        let counter = runs.clone();
+        scheduler.replace_handler(id, move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })?;
+        assert_eq!(scheduler.next_run(), Some(first));
+        scheduler.run_pending_at(first)?;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(scheduler.jobs[0].last_run, Some(first));
+
+        // A job that was not staged and has lost its handler is an error, not skipped
+        scheduler.jobs[0].handler = None;
+        let next = scheduler.jobs[0].next_run.unwrap();
+        assert!(matches!(scheduler.run_pending_at(next), Err(JobSchedulerError::HandlerNotBuilt)));
         Ok(())
     }
 
     #[test]
-    fn test_random_schedule() -> Result<(), JobSchedulerError> {
+    fn test_default_timezone() -> Result<(), JobSchedulerError> {
+        use chrono::{DateTime, Timelike};
+        let job = |name: &str| JobBuilder::new(name).cron("0 0 9 * * * *").add_handler(dummy_handler).build();
+        let local_hour = |job: &Job, timezone: Tz| DateTime::<Utc>::from(job.next_run.unwrap()).with_timezone(&timezone).hour();
+
         let mut scheduler = Scheduler::new();
-        
-        // Create random scheduled job with fixed times for predictable testing
-        let start = SystemTime::now() + Duration::from_secs(1);
-        let end = SystemTime::now() + Duration::from_secs(5);
-        
-        let job = JobBuilder::new("random-job")
-            .random(ScheduleTime::At(start), ScheduleTime::At(end))
+        scheduler.add_job(job("before"))?;
+        let mut scheduler = scheduler.default_timezone(Tz::Asia__Tokyo);
+        scheduler.add_job(job("inherits"))?;
+        scheduler.add_job(JobBuilder::new("own-zone")
+            .cron_tz("0 0 9 * * * *", Tz::Europe__London)
             .add_handler(dummy_handler)
-            .build();
-            
-        scheduler.add_job(job)?;
-        
-        assert_eq!(scheduler.jobs.len(), 1);
-        assert!(scheduler.jobs[0].next_run.is_some());
-        
-        // The random time should be between start and end
-        let next_run = scheduler.jobs[0].next_run.unwrap();
-        assert!(next_run >= start && next_run <= end);
-        
+            .build())?;
+
+        // Jobs added before the default stay on UTC
+        assert!(matches!(scheduler.jobs[0].schedules[0].schedule_type, ScheduleType::Cron { .. }));
+        assert_eq!(local_hour(&scheduler.jobs[0], Tz::UTC), 9);
+        // Later plain cron jobs inherit the default
+        assert_eq!(scheduler.jobs[1].schedules[0].schedule_type.kind(), "zoned_cron");
+        assert_eq!(local_hour(&scheduler.jobs[1], Tz::Asia__Tokyo), 9);
+        assert_eq!(local_hour(&scheduler.jobs[1], Tz::UTC), 0);
+        let ScheduleType::ZonedCron(zoned) = &scheduler.jobs[1].schedules[0].schedule_type else {
+            unreachable!();
+        };
+        assert_eq!(zoned.source, "0 0 9 * * * *");
+        assert_eq!(scheduler.jobs[1].schedules[0].schedule_type.to_string(), "cron 0 0 9 * * * * in Asia/Tokyo");
+        // A job's own zone wins over the default
+        assert_eq!(local_hour(&scheduler.jobs[2], Tz::Europe__London), 9);
         Ok(())
     }
 }
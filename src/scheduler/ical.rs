@@ -0,0 +1,116 @@
+//! iCalendar (RFC 5545) export of a scheduler's projected runs, enabled by the `ical` feature.
+//!
+//! Lets operators load upcoming job runs into a calendar app. Each projected run becomes its
+//! own `VEVENT`, so cron and recurring jobs expand into one event per occurrence.
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+use chrono::{DateTime, Utc};
+
+use super::base::Scheduler;
+
+// Longest content line allowed before it must be folded, in octets
+const MAX_LINE_OCTETS: usize = 75;
+
+impl Scheduler {
+    /// Render every projected run within `window` from now as an iCalendar document.
+    ///
+    /// Emits one `VEVENT` per run, summarised with the job's name, or its id if unnamed.
+    /// Like `timeline`, this is a projection and includes overdue runs.
+    pub fn export_ical(&self, window: Duration) -> String {
+        let names: HashMap<_, _> = self.list_all_jobs()
+            .into_iter()
+            .map(|job| (job.id, job.name.clone().unwrap_or_else(|| job.id.to_string())))
+            .collect();
+        let stamp = format_ical_time(SystemTime::now());
+        let mut out = String::new();
+        push_line(&mut out, "BEGIN:VCALENDAR");
+        push_line(&mut out, "VERSION:2.0");
+        push_line(&mut out, "PRODID:-//siafu//siafu//EN");
+        for (id, time) in self.timeline(window) {
+            let start = format_ical_time(time);
+            push_line(&mut out, "BEGIN:VEVENT");
+            push_line(&mut out, &format!("UID:{}-{}@siafu", id, start));
+            push_line(&mut out, &format!("DTSTAMP:{}", stamp));
+            push_line(&mut out, &format!("DTSTART:{}", start));
+            push_line(&mut out, &format!("SUMMARY:{}", escape_text(&names[&id])));
+            push_line(&mut out, "END:VEVENT");
+        }
+        push_line(&mut out, "END:VCALENDAR");
+        out
+    }
+}
+
+// UTC date-time in the iCalendar basic format, e.g. 20250101T120000Z
+fn format_ical_time(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+// Escape characters with special meaning in iCalendar text values
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(ch);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+// Append a content line ending in CRLF, folding it so no line exceeds the octet limit
+fn push_line(out: &mut String, line: &str) {
+    let mut octets = 0;
+    for ch in line.chars() {
+        if octets + ch.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            // The leading space of a continuation line counts towards its length
+            octets = 1;
+        }
+        out.push(ch);
+        octets += ch.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job::JobBuilder;
+    use crate::scheduler::types::RecurringInterval;
+    use crate::utils::time::ScheduleTime;
+
+    #[test]
+    fn test_export_ical() {
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(JobBuilder::new("backup; nightly")
+            .recurring(RecurringInterval::Hourly(1), Some(ScheduleTime::Delay(Duration::from_secs(60))))
+            .add_handler(|| {})
+            .build()).unwrap();
+
+        let ical = scheduler.export_ical(Duration::from_secs(3 * 3600));
+        assert!(ical.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ical.ends_with("END:VCALENDAR\r\n"));
+        // The recurring job expands into one event per occurrence
+        assert_eq!(ical.matches("BEGIN:VEVENT").count(), 3);
+        assert_eq!(ical.matches("SUMMARY:backup\\; nightly\r\n").count(), 3);
+        assert!(ical.lines().all(|line| line.len() <= MAX_LINE_OCTETS));
+    }
+
+    #[test]
+    fn test_long_lines_are_folded() {
+        let mut out = String::new();
+        push_line(&mut out, &format!("SUMMARY:{}", "x".repeat(100)));
+        let lines: Vec<&str> = out.split("\r\n").collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].len(), MAX_LINE_OCTETS);
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(lines[2], "");
+        assert_eq!(format_ical_time(SystemTime::UNIX_EPOCH), "19700101T000000Z");
+    }
+}
@@ -0,0 +1,119 @@
+//! Prometheus text-format metrics for a scheduler's jobs, enabled by the `prometheus` feature.
+//!
+//! Rendered with plain string formatting, so exposing them needs no client library: serve
+//! the output of `prometheus_metrics` from the process's metrics endpoint.
+
+use std::fmt::Write;
+use std::time::SystemTime;
+
+use super::base::Scheduler;
+use crate::job::{Job, JobState};
+
+impl Scheduler {
+    /// Render job counts and per-job runs, failures and time to next run as Prometheus
+    /// text-format metrics.
+    ///
+    /// Per-job series are labelled with the job's id and name, or an empty name if unnamed.
+    /// Jobs without an upcoming run have no `siafu_job_next_run_seconds` sample.
+    pub fn prometheus_metrics(&self) -> String {
+        self.prometheus_metrics_at(SystemTime::now())
+    }
+
+    // `prometheus_metrics` measuring time to the next run from `now`
+    fn prometheus_metrics_at(&self, now: SystemTime) -> String {
+        let jobs = self.list_all_jobs();
+        let mut out = String::new();
+
+        out.push_str("# HELP siafu_jobs Number of jobs held by the scheduler, by state.\n");
+        out.push_str("# TYPE siafu_jobs gauge\n");
+        for (state, label) in [(JobState::Active, "active"), (JobState::Paused, "paused"), (JobState::Completed, "completed")] {
+            let count = jobs.iter().filter(|job| job.state() == state).count();
+            let _ = writeln!(out, "siafu_jobs{{state=\"{}\"}} {}", label, count);
+        }
+
+        out.push_str("# HELP siafu_job_runs_total Runs of the job, including failed ones.\n");
+        out.push_str("# TYPE siafu_job_runs_total counter\n");
+        for job in &jobs {
+            let _ = writeln!(out, "siafu_job_runs_total{{{}}} {}", labels(job), job.total_run_count());
+        }
+
+        out.push_str("# HELP siafu_job_failures_total Failed runs of the job, including retried attempts.\n");
+        out.push_str("# TYPE siafu_job_failures_total counter\n");
+        for job in &jobs {
+            let _ = writeln!(out, "siafu_job_failures_total{{{}}} {}", labels(job), job.total_failures());
+        }
+
+        out.push_str("# HELP siafu_job_next_run_seconds Seconds until the job's next run, negative if overdue.\n");
+        out.push_str("# TYPE siafu_job_next_run_seconds gauge\n");
+        for job in &jobs {
+            let Some(next) = job.next_run else {
+                continue;
+            };
+            let seconds = match next.duration_since(now) {
+                Ok(ahead) => ahead.as_secs_f64(),
+                Err(err) => -err.duration().as_secs_f64(),
+            };
+            let _ = writeln!(out, "siafu_job_next_run_seconds{{{}}} {}", labels(job), seconds);
+        }
+        out
+    }
+}
+
+// Label set identifying a job
+fn labels(job: &Job) -> String {
+    format!("job_id=\"{}\",job_name=\"{}\"", job.id, escape_label(job.name.as_deref().unwrap_or("")))
+}
+
+// Escape a label value as the text format requires
+fn escape_label(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::error::Error as JobSchedulerError;
+    use crate::job::JobBuilder;
+    use crate::utils::time::ScheduleTime;
+
+    #[test]
+    fn test_prometheus_metrics() -> Result<(), JobSchedulerError> {
+        let mut scheduler = Scheduler::new();
+        scheduler.on_any_error(|_, _| {});
+        let job = JobBuilder::new("say \"hi\"")
+            .once(ScheduleTime::Delay(Duration::from_secs(60)))
+            .add_handler(|| panic!("failed"))
+            .build();
+        let id = job.id;
+        let due = job.next_run.unwrap();
+        scheduler.add_job(job)?;
+        let waiting = JobBuilder::new_unnamed()
+            .once(ScheduleTime::At(due + Duration::from_secs(90)))
+            .add_handler(|| {})
+            .build();
+        let waiting_id = waiting.id;
+        scheduler.add_job(waiting)?;
+        scheduler.run_pending_at(due)?;
+
+        let metrics = scheduler.prometheus_metrics_at(due);
+        let labels = format!("job_id=\"{}\",job_name=\"say \\\"hi\\\"\"", id);
+        assert!(metrics.contains("siafu_jobs{state=\"active\"} 1\n"));
+        assert!(metrics.contains("siafu_jobs{state=\"completed\"} 1\n"));
+        assert!(metrics.contains(&format!("siafu_job_runs_total{{{}}} 1\n", labels)));
+        assert!(metrics.contains(&format!("siafu_job_failures_total{{{}}} 1\n", labels)));
+        assert!(metrics.contains(&format!("siafu_job_next_run_seconds{{job_id=\"{}\",job_name=\"\"}} 90\n", waiting_id)));
+        // The finished job has no next run to report
+        assert!(!metrics.contains(&format!("siafu_job_next_run_seconds{{{}}}", labels)));
+        Ok(())
+    }
+}
@@ -4,62 +4,755 @@
 //! - `Schedule`: Contains schedule metadata including max runs and run count.
 //! - `RecurringSchedule` and `RandomSchedule`: Details for recurring and random patterns.
 //! - `RecurringInterval`: Preset intervals or custom frequency values.
+//! - `ZonedCronSchedule` and `DstPolicy`: Cron schedules evaluated in a timezone.
+//! - `NthCronSchedule`: Cron schedules that run on every `n`th match.
+//! - `BusinessDayCronSchedule`: Cron schedules that skip weekends and holidays.
 //!
 //! # Examples
 //!
 //! ```rust
-//! use siafu::scheduler::types::{Schedule, ScheduleType, RecurringInterval};
+//! use siafu::scheduler::types::{Schedule, ScheduleType, RecurringInterval, RecurringSchedule};
 //! use siafu::utils::time::ScheduleTime;
 //! use std::time::{SystemTime, Duration};
 //! use cron::Schedule as CronSchedule;
+//! use std::str::FromStr;
 //!
 //! // One-time schedule at a specific SystemTime
 //! let t = SystemTime::now() + Duration::from_secs(10);
-//! let once = Schedule { schedule_type: ScheduleType::Once(t), max_runs: Some(1), run_count: 0 };
+//! let once = Schedule { schedule_type: ScheduleType::Once(t), max_runs: Some(1), run_count: 0, next_run: Some(t) };
 //!
 //! // Recurring schedule every 5 seconds
 //! let recur = Schedule {
 //!     schedule_type: ScheduleType::Recurring(
-//!         RecurringSchedule { interval: RecurringInterval::Secondly(5), next_run: t }
+//!         RecurringSchedule::new(RecurringInterval::Secondly(5), t)
 //!     ),
 //!     max_runs: None,
 //!     run_count: 0,
+//!     next_run: Some(t),
 //! };
 //!
 //! // Cron schedule: every hour on the hour
 //! let cron_expr = "0 0 * * * * *";
 //! let cron_schedule = CronSchedule::from_str(cron_expr).unwrap();
-//! let cron = Schedule { schedule_type: ScheduleType::Cron(cron_schedule), max_runs: None, run_count: 0 };
+//! let first = cron_schedule.upcoming(chrono::Utc).next().map(SystemTime::from);
+//! let cron = Schedule {
+//!     schedule_type: ScheduleType::Cron { source: cron_expr.to_string(), schedule: cron_schedule },
+//!     max_runs: None,
+//!     run_count: 0,
+//!     next_run: first,
+//! };
 //! ```
 
-use std::time::SystemTime;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use chrono::{DateTime, Datelike, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeDelta, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
 use cron::Schedule as CronSchedule;
+use humantime::{format_duration, format_rfc3339_seconds};
+use rand::Rng;
+use uuid::Uuid;
+
+use crate::error::Error as JobSchedulerError;
+use crate::utils::time::random_time_between;
 
+#[derive(Clone)]
 pub enum ScheduleType {
     Once(SystemTime),
     Recurring(RecurringSchedule),
     Random(RandomSchedule),
-    Cron(CronSchedule),
+    RandomWindow(RandomWindowSchedule),
+    /// Cron schedule matched against UTC, keeping the expression it was parsed from so the
+    /// schedule can be written out and parsed again.
+    Cron { source: String, schedule: CronSchedule },
+    ZonedCron(ZonedCronSchedule),
+    CronEveryNth(NthCronSchedule),
+    AfterJob(AfterJobSchedule),
+    BusinessDayCron(BusinessDayCronSchedule),
+    Immediate,
+    Opportunistic(OpportunisticSchedule),
+    Dynamic(Arc<dyn DynamicSchedule>),
+    AtTimes(DailyTimesSchedule),
 }
 
+#[derive(Clone)]
 pub struct Schedule {
     pub schedule_type: ScheduleType,
     pub max_runs: Option<u32>,
     pub run_count: u32,
+    /// The next occurrence of this schedule, or `None` if it will not fire again.
+    pub next_run: Option<SystemTime>,
+}
+
+impl ScheduleType {
+    /// Short name of the variant, e.g. "cron" or "recurring", for telemetry.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ScheduleType::Once(_) => "once",
+            ScheduleType::Recurring(_) => "recurring",
+            ScheduleType::Random(_) => "random",
+            ScheduleType::RandomWindow(_) => "random_window",
+            ScheduleType::Cron { .. } => "cron",
+            ScheduleType::ZonedCron(_) => "zoned_cron",
+            ScheduleType::CronEveryNth(_) => "cron_every_nth",
+            ScheduleType::AfterJob(_) => "after_job",
+            ScheduleType::BusinessDayCron(_) => "business_day_cron",
+            ScheduleType::Immediate => "immediate",
+            ScheduleType::Opportunistic(_) => "opportunistic",
+            ScheduleType::Dynamic(_) => "dynamic",
+            ScheduleType::AtTimes(_) => "at_times",
+        }
+    }
+
+    // Whether two schedules would always fire at the same times. Random schedules never
+    // duplicate each other since each picks its own time.
+    pub(crate) fn duplicates(&self, other: &ScheduleType) -> bool {
+        match (self, other) {
+            (ScheduleType::Once(a), ScheduleType::Once(b)) => a == b,
+            (ScheduleType::Recurring(a), ScheduleType::Recurring(b)) => {
+                a.interval == b.interval && a.next_run == b.next_run && a.fixed_delay == b.fixed_delay
+            }
+            (ScheduleType::Cron { schedule: a, .. }, ScheduleType::Cron { schedule: b, .. }) => a == b,
+            (ScheduleType::CronEveryNth(a), ScheduleType::CronEveryNth(b)) => a.schedule == b.schedule && a.n == b.n,
+            (ScheduleType::AfterJob(a), ScheduleType::AfterJob(b)) => a == b,
+            (ScheduleType::Immediate, ScheduleType::Immediate) => true,
+            (ScheduleType::BusinessDayCron(a), ScheduleType::BusinessDayCron(b)) => {
+                a.schedule == b.schedule && a.holidays == b.holidays
+            }
+            (ScheduleType::AtTimes(a), ScheduleType::AtTimes(b)) => a.times == b.times && a.timezone == b.timezone,
+            (ScheduleType::ZonedCron(a), ScheduleType::ZonedCron(b)) => {
+                a.schedule == b.schedule && a.timezone == b.timezone && a.dst_policy == b.dst_policy
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for ScheduleType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleType::Once(time) => write!(f, "once at {}", format_rfc3339_seconds(*time)),
+            ScheduleType::Recurring(recurring) => {
+                match recurring.interval {
+                    RecurringInterval::Yearly(1) => write!(f, "every year")?,
+                    RecurringInterval::Yearly(years) => write!(f, "every {} years", years)?,
+                    _ => write!(f, "every {}", format_duration(recurring.interval.delta()))?,
+                }
+                if recurring.fixed_delay {
+                    write!(f, " after each run")?;
+                }
+                Ok(())
+            }
+            ScheduleType::Random(random) => write!(
+                f,
+                "once between {} and {}{}",
+                format_rfc3339_seconds(random.start_time),
+                format_rfc3339_seconds(random.end_time),
+                match random.distribution {
+                    Distribution::Uniform => "",
+                    Distribution::FrontLoaded => ", weighted early",
+                    Distribution::BackLoaded => ", weighted late",
+                }
+            ),
+            ScheduleType::RandomWindow(window) => write!(
+                f,
+                "every {} between {} and {} UTC",
+                format_duration(window.interval.delta().max(Duration::from_secs(86400))),
+                window.window_start,
+                window.window_end
+            ),
+            ScheduleType::Cron { source, .. } => write!(f, "cron {}", source),
+            ScheduleType::ZonedCron(zoned) => write!(f, "cron {} in {}", zoned.source, zoned.timezone),
+            ScheduleType::CronEveryNth(nth) => write!(f, "cron {}, every {} matches", nth.schedule, nth.n),
+            ScheduleType::BusinessDayCron(business) => write!(f, "cron {} on business days", business.schedule),
+            ScheduleType::AfterJob(after) => write!(f, "{} after job {} runs", format_duration(after.delay), after.job_id),
+            ScheduleType::Immediate => write!(f, "once, on the next sweep"),
+            ScheduleType::Opportunistic(opportunistic) => {
+                write!(f, "once when load allows, by {}", format_rfc3339_seconds(opportunistic.deadline))
+            }
+            ScheduleType::Dynamic(dynamic) => write!(f, "{}", dynamic.describe()),
+            ScheduleType::AtTimes(daily) => {
+                let times: Vec<String> = daily.times.iter().map(|time| time.format("%H:%M:%S").to_string()).collect();
+                write!(f, "daily at {} in {}", times.join(", "), daily.timezone)
+            }
+        }
+    }
+}
+
+impl Schedule {
+    /// The schedule's next occurrence as computed from its rule, without changing it.
+    ///
+    /// One-off schedules (`Once` and `Random`) have no rule to compute from and return
+    /// `None`, as does a schedule that has used up its `max_runs`.
+    pub fn peek_next_run(&self) -> Option<SystemTime> {
+        if let Some(max) = self.max_runs
+            && self.run_count >= max
+        {
+            return None;
+        }
+        match &self.schedule_type {
+            ScheduleType::Once(_) => None,
+            ScheduleType::Random(_) => None,
+            ScheduleType::Recurring(rec) => Some(rec.next_run),
+            ScheduleType::RandomWindow(_) => self.next_run,
+            ScheduleType::Cron { schedule, .. } => schedule.upcoming(Utc).next().map(|dt| dt.into()),
+            ScheduleType::ZonedCron(zoned) => zoned.next_after(SystemTime::now()),
+            ScheduleType::CronEveryNth(_) => self.next_run,
+            ScheduleType::AfterJob(_) => self.next_run,
+            ScheduleType::BusinessDayCron(business) => business.next_after(SystemTime::now()),
+            ScheduleType::Immediate => None,
+            ScheduleType::Opportunistic(_) => None,
+            ScheduleType::Dynamic(dynamic) => dynamic.next_after(SystemTime::now()),
+            ScheduleType::AtTimes(daily) => daily.next_after(SystemTime::now()),
+        }
+    }
+
+    /// Move past the current occurrence to the next one, returning the new `next_run`.
+    ///
+    /// Call after the schedule fires, once `run_count` has been incremented for the run.
+    pub fn advance(&mut self) -> Option<SystemTime> {
+        self.advance_at(SystemTime::now())
+    }
+
+    /// Like `advance`, treating `now` as the current time.
+    pub fn advance_at(&mut self, now: SystemTime) -> Option<SystemTime> {
+        self.next_run = self.following_run(now);
+        self.next_run
+    }
+
+    fn following_run(&mut self, now: SystemTime) -> Option<SystemTime> {
+        let previous = self.next_run;
+        if let Some(max_runs) = self.max_runs
+            && self.run_count >= max_runs
+        {
+            return None;
+        }
+
+        match &mut self.schedule_type {
+            ScheduleType::Once(_time) => None, // Runs once, no next run
+            ScheduleType::Random(_) => None, // Runs once at the pre-calculated time, no next run
+            ScheduleType::Recurring(recurring) => {
+                // update next_run; a zero delta (burst) leaves it due, but each sweep only
+                // advances a schedule once so it cannot fire repeatedly within one sweep
+                // A run too far out to represent ends the schedule
+                let next = recurring.following(recurring.next_run)?;
+                recurring.next_run = next;
+                Some(next)
+            }
+            ScheduleType::RandomWindow(window) => {
+                window.advance_cycle();
+                let (start, end) = window.window();
+                random_time_between(&mut rand::rng(), start, end)
+            }
+            ScheduleType::Cron { schedule, .. } => {
+                // Advance past the occurrence that just fired; missed occurrences are skipped
+                let after = DateTime::<Utc>::from(previous.map_or(now, |prev| now.max(prev)));
+                schedule.after(&after).next().map(|dt| dt.into())
+            }
+            ScheduleType::ZonedCron(zoned) => {
+                zoned.next_after(previous.map_or(now, |prev| now.max(prev)))
+            }
+            ScheduleType::CronEveryNth(nth) => nth.next_after(previous.unwrap_or(now), now),
+            ScheduleType::BusinessDayCron(business) => {
+                business.next_after(previous.map_or(now, |prev| now.max(prev)))
+            }
+            // Re-armed by the scheduler when the other job next runs
+            ScheduleType::AfterJob(_) => None,
+            ScheduleType::Immediate => None, // Runs once, no next run
+            ScheduleType::Opportunistic(_) => None,
+            ScheduleType::Dynamic(dynamic) => dynamic.next_after(previous.map_or(now, |prev| now.max(prev))),
+            ScheduleType::AtTimes(daily) => daily.next_after(previous.map_or(now, |prev| now.max(prev))),
+        }
+    }
+
+    // Next occurrence, respecting max_runs
+    pub(crate) fn pending_run(&self) -> Option<SystemTime> {
+        if let Some(max) = self.max_runs
+            && self.run_count >= max
+        {
+            return None;
+        }
+        self.next_run
+    }
+
+    // Reject cron schedules whose expression has no upcoming match, e.g. 30 February
+    pub(crate) fn validate(&self) -> Result<(), JobSchedulerError> {
+        let cron = match &self.schedule_type {
+            ScheduleType::Cron { schedule, .. } => schedule,
+            ScheduleType::ZonedCron(zoned) => &zoned.schedule,
+            ScheduleType::CronEveryNth(nth) => &nth.schedule,
+            ScheduleType::BusinessDayCron(business) => &business.schedule,
+            _ => return Ok(()),
+        };
+        if cron.upcoming(Utc).next().is_none() {
+            return Err(JobSchedulerError::InvalidSchedule(format!(
+                "{} has no upcoming occurrence",
+                self.schedule_type
+            )));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Clone)]
 pub struct RecurringSchedule {
     pub interval: RecurringInterval,
     pub next_run: SystemTime,
+    // Time the schedule started from, normally its first run
+    anchor: SystemTime,
+    /// Count each interval from when the previous run completed rather than from when it
+    /// was scheduled.
+    pub fixed_delay: bool,
+}
+
+impl RecurringSchedule {
+    /// A fixed-rate schedule whose first run is `first_run`.
+    pub fn new(interval: RecurringInterval, first_run: SystemTime) -> Self {
+        Self { interval, next_run: first_run, anchor: first_run, fixed_delay: false }
+    }
+
+    /// Time the schedule started from, normally its first run. `Yearly` intervals keep its
+    /// day of the month, so runs clamped to 28 February return to the 29th in leap years.
+    pub fn anchor(&self) -> SystemTime {
+        self.anchor
+    }
+
+    /// The run one interval after `time`, or `None` if it cannot be represented.
+    ///
+    /// `Yearly` intervals step by calendar years, keeping the anchor's day of the month where
+    /// it exists; other intervals add their fixed `delta`.
+    pub fn following(&self, time: SystemTime) -> Option<SystemTime> {
+        match self.interval {
+            RecurringInterval::Yearly(years) => add_years(time, years, DateTime::<Utc>::from(self.anchor).day()),
+            _ => time.checked_add(self.interval.delta()),
+        }
+    }
+}
+
+// `years` calendar years after `time` at the same UTC time of day, on day `day` of the
+// month or the month's last day if it is shorter
+pub(crate) fn add_years(time: SystemTime, years: u32, day: u32) -> Option<SystemTime> {
+    let time = DateTime::<Utc>::from(time);
+    let year = time.year().checked_add(i32::try_from(years).ok()?)?;
+    let date = (1..=day).rev().find_map(|day| NaiveDate::from_ymd_opt(year, time.month(), day))?;
+    Some(date.and_time(time.time()).and_utc().into())
 }
 
+#[derive(Clone)]
 pub struct RandomSchedule {
     pub start_time: SystemTime,
     pub end_time: SystemTime,
+    /// How the run time is spread across the window.
+    pub distribution: Distribution,
+}
+
+/// How a random schedule's run time is spread across its window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Distribution {
+    /// Every time in the window is equally likely.
+    #[default]
+    Uniform,
+    /// Times near the start of the window are more likely, falling off linearly to the end.
+    FrontLoaded,
+    /// Times near the end of the window are more likely, rising linearly from the start.
+    BackLoaded,
+}
+
+impl Distribution {
+    // Pick a time in `[start, end)` following the distribution. The earlier or later of two
+    // uniform picks has a linearly falling or rising density.
+    pub(crate) fn sample_between<R: Rng + ?Sized>(&self, rng: &mut R, start: SystemTime, end: SystemTime) -> Option<SystemTime> {
+        let first = random_time_between(rng, start, end)?;
+        match self {
+            Distribution::Uniform => Some(first),
+            Distribution::FrontLoaded => Some(first.min(random_time_between(rng, start, end)?)),
+            Distribution::BackLoaded => Some(first.max(random_time_between(rng, start, end)?)),
+        }
+    }
+}
+
+/// Recurring schedule that runs at a random time within a daily window each cycle.
+///
+/// Window times are UTC. A window whose end is at or before its start wraps past midnight.
+#[derive(Clone)]
+pub struct RandomWindowSchedule {
+    pub interval: RecurringInterval,
+    pub window_start: NaiveTime,
+    pub window_end: NaiveTime,
+    /// Midnight (UTC) of the day the current cycle's window opens on.
+    pub cycle_day: SystemTime,
+}
+
+impl RandomWindowSchedule {
+    // Bounds of the current cycle's window
+    pub(crate) fn window(&self) -> (SystemTime, SystemTime) {
+        let since_midnight = |time: NaiveTime| (time - NaiveTime::MIN).to_std().unwrap_or_default();
+        let start = self.cycle_day + since_midnight(self.window_start);
+        let mut end = self.cycle_day + since_midnight(self.window_end);
+        if end <= start {
+            end += Duration::from_secs(86400);
+        }
+        (start, end)
+    }
+
+    // Move on to the next cycle's day
+    pub(crate) fn advance_cycle(&mut self) {
+        // There is one window a day, so shorter intervals would pick from the same window again
+        self.cycle_day += self.interval.delta().max(Duration::from_secs(86400));
+        self.cycle_day = midnight_utc(self.cycle_day);
+    }
+}
+
+// Start of the UTC day containing `time`
+pub(crate) fn midnight_utc(time: SystemTime) -> SystemTime {
+    DateTime::<Utc>::from(time)
+        .date_naive()
+        .and_time(NaiveTime::MIN)
+        .and_utc()
+        .into()
+}
+
+/// Schedule that runs a fixed delay after another job's last successful run.
+///
+/// Has no run of its own until the other job runs; the scheduler then sets the next run to
+/// that job's last run plus `delay`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AfterJobSchedule {
+    pub job_id: Uuid,
+    pub delay: Duration,
+}
+
+/// Schedule that runs once, as soon as `load_check` allows, and at `deadline` regardless.
+///
+/// While the check returns false the scheduler keeps re-asking it, at most every max poll
+/// interval, until the deadline.
+#[derive(Clone)]
+pub struct OpportunisticSchedule {
+    pub deadline: SystemTime,
+    pub load_check: Arc<dyn Fn() -> bool + Send + Sync>,
+}
+
+impl OpportunisticSchedule {
+    /// Whether the job may run at `now`: the load check passes or the deadline has arrived.
+    pub fn ready_at(&self, now: SystemTime) -> bool {
+        now >= self.deadline || (self.load_check)()
+    }
+}
+
+/// Custom scheduling logic, e.g. runs only during market hours, for use with
+/// `JobBuilder::dynamic`.
+///
+/// # Examples
+///
+/// ```rust
+/// use siafu::scheduler::types::DynamicSchedule;
+/// use std::time::{Duration, SystemTime};
+///
+/// // Every 90 seconds
+/// struct EveryNinetySeconds;
+///
+/// impl DynamicSchedule for EveryNinetySeconds {
+///     fn next_after(&self, after: SystemTime) -> Option<SystemTime> {
+///         after.checked_add(Duration::from_secs(90))
+///     }
+/// }
+/// ```
+pub trait DynamicSchedule: Send + Sync {
+    /// The first run strictly after `after`, or `None` if the schedule has ended.
+    fn next_after(&self, after: SystemTime) -> Option<SystemTime>;
+
+    /// Human-readable description, used when displaying the schedule.
+    fn describe(&self) -> String {
+        "custom schedule".to_string()
+    }
+}
+
+/// Schedule that runs every day at each of a list of local times in `timezone`.
+///
+/// Local times skipped by a daylight saving change run once the clocks have gone forward;
+/// those repeated run once, at the earlier instant.
+#[derive(Clone)]
+pub struct DailyTimesSchedule {
+    /// Times of day to run at, sorted and without duplicates.
+    pub times: Vec<NaiveTime>,
+    pub timezone: Tz,
+}
+
+impl DailyTimesSchedule {
+    /// The first listed time strictly after `after`, moving on to the next day after the
+    /// day's last time. Returns `None` if there are no times.
+    pub fn next_after(&self, after: SystemTime) -> Option<SystemTime> {
+        let after = DateTime::<Utc>::from(after);
+        let today = after.with_timezone(&self.timezone).date_naive();
+        // Start a day early, as a DST change can move a local time across midnight UTC
+        today.pred_opt()?
+            .iter_days()
+            .take(3)
+            .flat_map(|day| self.times.iter().map(move |time| day.and_time(*time)))
+            .map(|local| self.resolve(local))
+            .find(|time| *time > after)
+            .map(SystemTime::from)
+    }
+
+    // Instant a local time maps to, taking the earlier of a repeated time and reading a
+    // skipped one with the offset in effect before the gap
+    fn resolve(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        match self.timezone.from_local_datetime(&local) {
+            LocalResult::Single(time) | LocalResult::Ambiguous(time, _) => time.with_timezone(&Utc),
+            LocalResult::None => {
+                let before = self.timezone.offset_from_utc_datetime(&(local - TimeDelta::days(1)));
+                (local - TimeDelta::seconds(before.fix().local_minus_utc() as i64)).and_utc()
+            }
+        }
+    }
+}
+
+/// Cron schedule that runs on every `n`th match of its expression, e.g. every other Monday.
+#[derive(Clone)]
+pub struct NthCronSchedule {
+    pub schedule: CronSchedule,
+    pub n: u32,
+}
+
+impl NthCronSchedule {
+    /// The match `n` matches after `previous`, stepping further in multiples of `n` until it
+    /// is after `now`.
+    pub fn next_after(&self, previous: SystemTime, now: SystemTime) -> Option<SystemTime> {
+        let step = self.n.max(1) as usize;
+        self.schedule.after(&DateTime::<Utc>::from(previous))
+            .skip(step - 1)
+            .step_by(step)
+            .map(SystemTime::from)
+            .find(|time| *time > now)
+    }
+}
+
+/// Cron schedule whose matches on weekends or holidays are skipped.
+///
+/// Weekends are Saturday and Sunday; days are UTC.
+#[derive(Clone)]
+pub struct BusinessDayCronSchedule {
+    pub schedule: CronSchedule,
+    pub holidays: Vec<NaiveDate>,
 }
 
-#[derive(Debug,Clone, PartialEq, Eq, PartialOrd, Ord)]
+// How far ahead to look for a business-day match before concluding there is none
+const MAX_BUSINESS_DAY_SEARCH: TimeDelta = TimeDelta::days(366);
+
+impl BusinessDayCronSchedule {
+    /// Whether `date` is a weekday that is not a holiday.
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.holidays.contains(&date)
+    }
+
+    /// The first match after `after` that falls on a business day.
+    ///
+    /// Returns `None` if there is no such match within a year.
+    pub fn next_after(&self, after: SystemTime) -> Option<SystemTime> {
+        let after = DateTime::<Utc>::from(after);
+        let limit = after + MAX_BUSINESS_DAY_SEARCH;
+        self.schedule.after(&after)
+            .take_while(|time| *time <= limit)
+            .find(|time| self.is_business_day(time.date_naive()))
+            .map(SystemTime::from)
+    }
+}
+
+/// Daily window of UTC times a job is allowed to run in.
+///
+/// The window includes `start` but not `end`. A window whose end is at or before its start
+/// wraps past midnight, e.g. 22:00-02:00.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl TimeWindow {
+    /// Whether `time` falls inside the window.
+    pub fn contains(&self, time: SystemTime) -> bool {
+        let time = DateTime::<Utc>::from(time).time();
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+
+    // The first time the window opens after `time`
+    fn next_start_after(&self, time: SystemTime) -> SystemTime {
+        let since_midnight = (self.start - NaiveTime::MIN).to_std().unwrap_or_default();
+        let today = midnight_utc(time) + since_midnight;
+        if today > time { today } else { today + Duration::from_secs(86400) }
+    }
+
+    // Move a schedule's next run that falls outside the window to the window's next opening,
+    // or for cron schedules to their first occurrence inside it
+    pub(crate) fn constrain(&self, sched: &mut Schedule) {
+        let Some(mut next) = sched.next_run else {
+            return;
+        };
+        // A year of daily windows without a matching occurrence means there never is one
+        for _ in 0..366 {
+            if self.contains(next) {
+                sched.next_run = Some(next);
+                return;
+            }
+            let opening = self.next_start_after(next);
+            let before_opening = opening - Duration::from_nanos(1);
+            next = match &mut sched.schedule_type {
+                ScheduleType::Cron { schedule: cron, .. } | ScheduleType::CronEveryNth(NthCronSchedule { schedule: cron, .. }) => {
+                    match cron.after(&DateTime::<Utc>::from(before_opening)).next() {
+                        Some(time) => time.into(),
+                        None => break,
+                    }
+                }
+                ScheduleType::BusinessDayCron(business) => match business.next_after(before_opening) {
+                    Some(time) => time,
+                    None => break,
+                },
+                ScheduleType::ZonedCron(zoned) => match zoned.next_after(before_opening) {
+                    Some(time) => time,
+                    None => break,
+                },
+                ScheduleType::Dynamic(dynamic) => match dynamic.next_after(before_opening) {
+                    Some(time) => time,
+                    None => break,
+                },
+                ScheduleType::AtTimes(daily) => match daily.next_after(before_opening) {
+                    Some(time) => time,
+                    None => break,
+                },
+                ScheduleType::Recurring(recurring) => {
+                    // Later runs keep the cadence from the window's opening
+                    recurring.next_run = opening;
+                    opening
+                }
+                // Pick again from the next cycle, rather than losing the randomness
+                ScheduleType::RandomWindow(window) => {
+                    window.advance_cycle();
+                    let (start, end) = window.window();
+                    match random_time_between(&mut rand::rng(), start, end) {
+                        Some(time) => time,
+                        None => break,
+                    }
+                }
+                // One-off runs are postponed to the opening
+                ScheduleType::Once(_) | ScheduleType::Random(_) | ScheduleType::AfterJob(_) => opening,
+                // Immediate runs and opportunistic deadlines hold at any time of day
+                ScheduleType::Immediate | ScheduleType::Opportunistic(_) => return,
+            };
+        }
+        sched.next_run = None;
+    }
+}
+
+/// How a timezone-aware cron schedule handles local times around daylight saving changes.
+///
+/// When clocks go forward some local times never occur (a gap); when they go back an hour
+/// of local times occurs twice (an overlap).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DstPolicy {
+    /// Don't run occurrences that fall in a gap or an overlap.
+    Skip,
+    /// Run gap occurrences once, shifted forward by the length of the gap, and overlap
+    /// occurrences once, at the earlier instant. This is the default.
+    #[default]
+    FireOnce,
+    /// Run gap occurrences as with `FireOnce`, and overlap occurrences at both instants.
+    FireBoth,
+}
+
+/// Cron schedule whose fields are matched against local time in `timezone`.
+#[derive(Clone)]
+pub struct ZonedCronSchedule {
+    /// Expression the schedule was parsed from, as for `ScheduleType::Cron`.
+    pub source: String,
+    pub schedule: CronSchedule,
+    pub timezone: Tz,
+    pub dst_policy: DstPolicy,
+}
+
+// Largest shift a DST change makes to local time, with some margin
+const MAX_DST_SHIFT: TimeDelta = TimeDelta::hours(3);
+
+impl ZonedCronSchedule {
+    /// The first occurrence strictly after `after`, resolved under the DST policy.
+    pub fn next_after(&self, after: SystemTime) -> Option<SystemTime> {
+        let after = DateTime::<Utc>::from(after);
+        // Start early enough that occurrences moved by a DST change are not missed. Local
+        // times are matched as if they were UTC, which has no gaps or overlaps.
+        let start = after.with_timezone(&self.timezone).naive_local() - MAX_DST_SHIFT;
+        let mut best: Option<(DateTime<Utc>, NaiveDateTime)> = None;
+        for local in self.schedule.after(&start.and_utc()) {
+            let local = local.naive_utc();
+            if let Some((_, found)) = best
+                && local > found + MAX_DST_SHIFT
+            {
+                break;
+            }
+            for time in self.resolve(local) {
+                if time > after && best.is_none_or(|(earliest, _)| time < earliest) {
+                    best = Some((time, local));
+                }
+            }
+            // Without a DST change in between, local and UTC order agree and the first match is the earliest
+            if let Some((time, _)) = best
+                && self.offset_at(after) == self.offset_at(time + MAX_DST_SHIFT)
+            {
+                break;
+            }
+        }
+        best.map(|(time, _)| time.into())
+    }
+
+    // Instants a local time maps to under the DST policy, earliest first
+    fn resolve(&self, local: NaiveDateTime) -> Vec<DateTime<Utc>> {
+        match (self.timezone.from_local_datetime(&local), self.dst_policy) {
+            (LocalResult::Single(time), _) => vec![time.with_timezone(&Utc)],
+            (_, DstPolicy::Skip) => Vec::new(),
+            (LocalResult::Ambiguous(earlier, _), DstPolicy::FireOnce) => vec![earlier.with_timezone(&Utc)],
+            (LocalResult::Ambiguous(earlier, later), DstPolicy::FireBoth) => {
+                vec![earlier.with_timezone(&Utc), later.with_timezone(&Utc)]
+            }
+            (LocalResult::None, _) => {
+                // Read the local time with the offset in effect before the gap
+                let before = self.timezone.offset_from_utc_datetime(&(local - TimeDelta::days(1)));
+                let offset = TimeDelta::seconds(before.fix().local_minus_utc() as i64);
+                vec![(local - offset).and_utc()]
+            }
+        }
+    }
+
+    fn offset_at(&self, time: DateTime<Utc>) -> i32 {
+        self.timezone.offset_from_utc_datetime(&time.naive_utc()).fix().local_minus_utc()
+    }
+}
+
+/// Shift applied to every run time computed for a job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunOffset {
+    pub delta: Duration,
+    /// Shift runs earlier rather than later.
+    pub before: bool,
+}
+
+impl RunOffset {
+    /// Apply the offset to a base run time.
+    ///
+    /// Shifting earlier never panics: a result that would precede the earliest representable
+    /// time is clamped to the Unix epoch. Shifted times in the past are simply due, so the
+    /// job fires on the next sweep.
+    pub fn apply(&self, time: SystemTime) -> SystemTime {
+        if self.before {
+            time.checked_sub(self.delta).unwrap_or(SystemTime::UNIX_EPOCH)
+        } else {
+            time.checked_add(self.delta).unwrap_or(time)
+        }
+    }
+}
+
+#[derive(Debug,Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum RecurringInterval {
     Secondly(u32), 
     Minutely(u32),
@@ -67,8 +760,358 @@ pub enum RecurringInterval {
     Daily(u32),    
     Weekly(u32),   
     Monthly(u32),  
+    Yearly(u32),
     Custom { 
         expression: String, 
         frequency: u32, 
     },
-}
\ No newline at end of file
+}
+
+impl RecurringInterval {
+    // Fixed time between runs of this interval
+    pub(crate) fn delta(&self) -> Duration {
+        match self {
+            RecurringInterval::Secondly(secs) => {
+                Duration::from_secs(*secs as u64)
+            },
+            RecurringInterval::Minutely(mins) => {
+                Duration::from_secs(60 * *mins as u64)
+            },
+            RecurringInterval::Hourly(hours) => {
+                Duration::from_secs(3600 * *hours as u64)
+            },
+            RecurringInterval::Daily(days) => {
+                Duration::from_secs(86400 * *days as u64)
+            },
+            RecurringInterval::Weekly(weeks) => {
+                Duration::from_secs(7 * 86400 * *weeks as u64)
+            },
+            RecurringInterval::Monthly(months) => {
+                Duration::from_secs(30 * 86400 * *months as u64)
+            },
+            RecurringInterval::Yearly(years) => {
+                Duration::from_secs(365 * 86400 * *years as u64)
+            },
+            RecurringInterval::Custom { expression, frequency } => {
+                let days = match expression.as_str() {
+                    "daily" => 1,
+                    "weekly" => 7,
+                    "monthly" => 30,
+                    _ => *frequency,
+                };
+                Duration::from_secs(days as u64 * 86400)
+            },
+        }
+    }
+
+    /// Exact length of the interval, or `None` for monthly and yearly intervals.
+    ///
+    /// Months differ in length, so `Monthly` (and `Custom` "monthly") have no exact duration.
+    /// The scheduler approximates a month as 30 days when advancing such schedules. `Yearly`
+    /// intervals step by calendar years instead.
+    pub fn to_duration(&self) -> Option<Duration> {
+        match self {
+            RecurringInterval::Monthly(_) | RecurringInterval::Yearly(_) => None,
+            RecurringInterval::Custom { expression, .. } if expression == "monthly" => None,
+            other => Some(other.delta()),
+        }
+    }
+
+    /// Choose the largest preset unit that evenly divides `duration`.
+    ///
+    /// Whole days become `Daily`, then `Hourly`, `Minutely`, and anything else `Secondly`.
+    /// Sub-second parts are dropped and counts beyond `u32::MAX` saturate.
+    pub fn from_duration(duration: Duration) -> RecurringInterval {
+        let secs = duration.as_secs();
+        let count = |unit: u64| u32::try_from(secs / unit).unwrap_or(u32::MAX);
+        if secs.is_multiple_of(86400) && secs > 0 {
+            RecurringInterval::Daily(count(86400))
+        } else if secs.is_multiple_of(3600) && secs > 0 {
+            RecurringInterval::Hourly(count(3600))
+        } else if secs.is_multiple_of(60) && secs > 0 {
+            RecurringInterval::Minutely(count(60))
+        } else {
+            RecurringInterval::Secondly(count(1))
+        }
+    }
+
+    /// Convert recognized `Custom` expressions into their canonical preset variant.
+    ///
+    /// `"daily"`, `"weekly"` and `"monthly"` become `Daily(1)`, `Weekly(1)` and `Monthly(1)`,
+    /// matching the delta the scheduler computes for them, so equivalent intervals compare
+    /// and hash identically. Other intervals are returned unchanged.
+    pub fn normalize(self) -> RecurringInterval {
+        match self {
+            RecurringInterval::Custom { expression, frequency } => match expression.as_str() {
+                "daily" => RecurringInterval::Daily(1),
+                "weekly" => RecurringInterval::Weekly(1),
+                "monthly" => RecurringInterval::Monthly(1),
+                _ => RecurringInterval::Custom { expression, frequency },
+            },
+            other => other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn custom(expression: &str, frequency: u32) -> RecurringInterval {
+        RecurringInterval::Custom { expression: expression.to_string(), frequency }
+    }
+
+    #[test]
+    fn test_business_day_cron_skips_weekends_and_holidays() {
+        let holiday = NaiveDate::from_ymd_opt(2025, 12, 25).unwrap();
+        let business = BusinessDayCronSchedule {
+            schedule: "0 0 9 * * * *".parse().unwrap(),
+            holidays: vec![holiday],
+        };
+        let at = |s: &str| SystemTime::from(DateTime::parse_from_rfc3339(s).unwrap());
+
+        // Wednesday 24 December runs, Christmas Day is skipped
+        assert_eq!(business.next_after(at("2025-12-24T08:00:00Z")), Some(at("2025-12-24T09:00:00Z")));
+        assert_eq!(business.next_after(at("2025-12-24T09:00:00Z")), Some(at("2025-12-26T09:00:00Z")));
+        // Friday's match is followed by Monday's
+        assert_eq!(business.next_after(at("2025-12-26T09:00:00Z")), Some(at("2025-12-29T09:00:00Z")));
+
+        // An expression matching only weekends never fires
+        let weekends = BusinessDayCronSchedule { schedule: "0 0 9 * * Sat *".parse().unwrap(), holidays: Vec::new() };
+        assert_eq!(weekends.next_after(at("2025-12-24T08:00:00Z")), None);
+    }
+
+    #[test]
+    fn test_weighted_distributions() {
+        let start = SystemTime::UNIX_EPOCH;
+        let end = start + Duration::from_secs(1000);
+        let mut rng = rand::rng();
+        // Mean position of samples as a fraction of the window: 1/2 uniform, 1/3 front-loaded,
+        // 2/3 back-loaded
+        let mut mean = |distribution: Distribution| {
+            let samples = 5000;
+            let total: f64 = (0..samples)
+                .map(|_| {
+                    let time = distribution.sample_between(&mut rng, start, end).unwrap();
+                    assert!(time >= start && time < end);
+                    time.duration_since(start).unwrap().as_secs_f64() / 1000.0
+                })
+                .sum();
+            total / samples as f64
+        };
+        assert!((mean(Distribution::Uniform) - 0.5).abs() < 0.03);
+        assert!((mean(Distribution::FrontLoaded) - 1.0 / 3.0).abs() < 0.03);
+        assert!((mean(Distribution::BackLoaded) - 2.0 / 3.0).abs() < 0.03);
+    }
+
+    fn london_cron(expr: &str, dst_policy: DstPolicy) -> ZonedCronSchedule {
+        ZonedCronSchedule {
+            source: expr.to_string(),
+            schedule: expr.parse().unwrap(),
+            timezone: chrono_tz::Europe::London,
+            dst_policy,
+        }
+    }
+
+    fn utc(s: &str) -> SystemTime {
+        DateTime::parse_from_rfc3339(s).unwrap().into()
+    }
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_time_window_contains() {
+        let day = TimeWindow { start: time(9, 0), end: time(17, 0) };
+        assert!(day.contains(utc("2030-01-01T09:00:00Z")));
+        assert!(!day.contains(utc("2030-01-01T17:00:00Z")));
+        assert!(!day.contains(utc("2030-01-01T03:00:00Z")));
+
+        let night = TimeWindow { start: time(22, 0), end: time(2, 0) };
+        assert!(night.contains(utc("2030-01-01T23:30:00Z")));
+        assert!(night.contains(utc("2030-01-01T01:59:00Z")));
+        assert!(!night.contains(utc("2030-01-01T12:00:00Z")));
+    }
+
+    #[test]
+    fn test_time_window_constrain_one_off() {
+        let day = TimeWindow { start: time(9, 0), end: time(17, 0) };
+        let night = utc("2030-01-01T03:00:00Z");
+        let one_off = |schedule_type| Schedule { schedule_type, max_runs: Some(1), run_count: 0, next_run: Some(night) };
+
+        let mut once = one_off(ScheduleType::Once(night));
+        day.constrain(&mut once);
+        assert_eq!(once.next_run, Some(utc("2030-01-01T09:00:00Z")));
+
+        // Runs promised regardless of the time of day keep their time
+        let mut immediate = one_off(ScheduleType::Immediate);
+        day.constrain(&mut immediate);
+        assert_eq!(immediate.next_run, Some(night));
+        let mut opportunistic = one_off(ScheduleType::Opportunistic(OpportunisticSchedule {
+            deadline: night,
+            load_check: Arc::new(|| false),
+        }));
+        day.constrain(&mut opportunistic);
+        assert_eq!(opportunistic.next_run, Some(night));
+
+        // Random windows pick again until a pick lands inside the active window
+        let mut random_window = one_off(ScheduleType::RandomWindow(RandomWindowSchedule {
+            interval: RecurringInterval::Daily(1),
+            window_start: time(8, 0),
+            window_end: time(10, 0),
+            cycle_day: utc("2030-01-01T00:00:00Z"),
+        }));
+        random_window.next_run = Some(utc("2030-01-01T08:30:00Z"));
+        random_window.max_runs = None;
+        day.constrain(&mut random_window);
+        let next = random_window.next_run.unwrap();
+        assert!(day.contains(next));
+        assert!(DateTime::<Utc>::from(next).time() < time(10, 0));
+    }
+
+    #[test]
+    fn test_zoned_cron_outside_dst_change() {
+        // 09:00 in London is 08:00 UTC during summer time
+        let cron = london_cron("0 0 9 * * * *", DstPolicy::default());
+        assert_eq!(cron.next_after(utc("2025-06-01T12:00:00Z")), Some(utc("2025-06-02T08:00:00Z")));
+    }
+
+    #[test]
+    fn test_zoned_cron_spring_forward_gap() {
+        // On 2025-03-30 London clocks jump from 01:00 to 02:00, so 01:30 does not exist
+        let after = utc("2025-03-29T12:00:00Z");
+
+        let skip = london_cron("0 30 1 * * * *", DstPolicy::Skip);
+        assert_eq!(skip.next_after(after), Some(utc("2025-03-31T00:30:00Z")));
+
+        for policy in [DstPolicy::FireOnce, DstPolicy::FireBoth] {
+            // Fires at 02:30 BST, an hour after the clocks changed
+            let cron = london_cron("0 30 1 * * * *", policy);
+            let first = cron.next_after(after).unwrap();
+            assert_eq!(first, utc("2025-03-30T01:30:00Z"));
+            assert_eq!(cron.next_after(first), Some(utc("2025-03-31T00:30:00Z")));
+        }
+    }
+
+    #[test]
+    fn test_zoned_cron_fall_back_overlap() {
+        // On 2025-10-26 London clocks go back from 02:00 to 01:00, so 01:30 occurs twice
+        let after = utc("2025-10-25T12:00:00Z");
+
+        let skip = london_cron("0 30 1 * * * *", DstPolicy::Skip);
+        assert_eq!(skip.next_after(after), Some(utc("2025-10-27T01:30:00Z")));
+
+        let once = london_cron("0 30 1 * * * *", DstPolicy::FireOnce);
+        let first = once.next_after(after).unwrap();
+        assert_eq!(first, utc("2025-10-26T00:30:00Z"));
+        assert_eq!(once.next_after(first), Some(utc("2025-10-27T01:30:00Z")));
+
+        let both = london_cron("0 30 1 * * * *", DstPolicy::FireBoth);
+        let first = both.next_after(after).unwrap();
+        assert_eq!(first, utc("2025-10-26T00:30:00Z"));
+        let second = both.next_after(first).unwrap();
+        assert_eq!(second, utc("2025-10-26T01:30:00Z"));
+        assert_eq!(both.next_after(second), Some(utc("2025-10-27T01:30:00Z")));
+    }
+
+    #[test]
+    fn test_schedule_type_display() {
+        let once = ScheduleType::Once(utc("2025-05-05T12:00:00Z"));
+        assert_eq!(once.to_string(), "once at 2025-05-05T12:00:00Z");
+        let recurring = ScheduleType::Recurring(RecurringSchedule::new(RecurringInterval::Minutely(5), SystemTime::now()));
+        assert_eq!(recurring.to_string(), "every 5m");
+        let cron = ScheduleType::ZonedCron(london_cron("0 0 9 * * * *", DstPolicy::default()));
+        assert_eq!(cron.to_string(), "cron 0 0 9 * * * * in Europe/London");
+    }
+
+    #[test]
+    fn test_duration_conversions() {
+        assert_eq!(RecurringInterval::from_duration(Duration::from_secs(2 * 86400)), RecurringInterval::Daily(2));
+        assert_eq!(RecurringInterval::from_duration(Duration::from_secs(7200)), RecurringInterval::Hourly(2));
+        assert_eq!(RecurringInterval::from_duration(Duration::from_secs(90)), RecurringInterval::Secondly(90));
+        assert_eq!(RecurringInterval::from_duration(Duration::from_secs(300)), RecurringInterval::Minutely(5));
+        for interval in [RecurringInterval::Secondly(45), RecurringInterval::Hourly(3), RecurringInterval::Weekly(1)] {
+            let duration = interval.to_duration().unwrap();
+            assert_eq!(RecurringInterval::from_duration(duration).to_duration(), Some(duration));
+        }
+        assert_eq!(RecurringInterval::Monthly(1).to_duration(), None);
+        assert_eq!(custom("monthly", 1).to_duration(), None);
+        assert_eq!(custom("fortnightly", 14).to_duration(), Some(Duration::from_secs(14 * 86400)));
+    }
+
+    #[test]
+    fn test_normalize_recognized_custom_expressions() {
+        assert_eq!(custom("daily", 1).normalize(), RecurringInterval::Daily(1));
+        assert_eq!(custom("weekly", 1).normalize(), RecurringInterval::Weekly(1));
+        assert_eq!(custom("monthly", 1).normalize(), RecurringInterval::Monthly(1));
+    }
+
+    #[test]
+    fn test_normalize_leaves_other_intervals_unchanged() {
+        assert_eq!(custom("fortnightly", 14).normalize(), custom("fortnightly", 14));
+        assert_eq!(RecurringInterval::Hourly(3).normalize(), RecurringInterval::Hourly(3));
+    }
+
+    #[test]
+    fn test_normalized_intervals_hash_identically() {
+        let mut set = HashSet::new();
+        set.insert(custom("weekly", 1).normalize());
+        set.insert(RecurringInterval::Weekly(1).normalize());
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn test_yearly_from_leap_day() {
+        let leap_day = Utc.with_ymd_and_hms(2024, 2, 29, 9, 0, 0).unwrap().into();
+        let mut sched = Schedule {
+            schedule_type: ScheduleType::Recurring(RecurringSchedule::new(RecurringInterval::Yearly(1), leap_day)),
+            max_runs: None,
+            run_count: 0,
+            next_run: Some(leap_day),
+        };
+        let expected = [(2025, 2, 28), (2026, 2, 28), (2027, 2, 28), (2028, 2, 29)];
+        for (year, month, day) in expected {
+            let next = sched.advance().unwrap();
+            assert_eq!(next, Utc.with_ymd_and_hms(year, month, day, 9, 0, 0).unwrap().into());
+        }
+        assert_eq!(sched.schedule_type.to_string(), "every year");
+        assert_eq!(RecurringInterval::Yearly(1).to_duration(), None);
+    }
+
+    #[test]
+    fn test_daily_times_wrap_to_next_day() {
+        let at = |h: u32| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        let daily = DailyTimesSchedule { times: vec![at(8), at(12), at(17)], timezone: chrono_tz::Europe::London };
+        // British Summer Time, an hour ahead of UTC
+        assert_eq!(daily.next_after(utc("2024-07-01T09:00:00Z")), Some(utc("2024-07-01T11:00:00Z")));
+        assert_eq!(daily.next_after(utc("2024-07-01T11:00:00Z")), Some(utc("2024-07-01T16:00:00Z")));
+        // Past the last time of the day
+        assert_eq!(daily.next_after(utc("2024-07-01T16:00:00Z")), Some(utc("2024-07-02T07:00:00Z")));
+        assert_eq!(daily.next_after(utc("2024-12-31T23:30:00Z")), Some(utc("2025-01-01T08:00:00Z")));
+        assert_eq!(
+            ScheduleType::AtTimes(daily).to_string(),
+            "daily at 08:00:00, 12:00:00, 17:00:00 in Europe/London"
+        );
+    }
+
+    #[test]
+    fn test_random_window_sub_daily_interval() {
+        let window = RandomWindowSchedule {
+            interval: RecurringInterval::Hourly(1),
+            window_start: NaiveTime::from_hms_opt(1, 0, 0).unwrap(),
+            window_end: NaiveTime::from_hms_opt(4, 0, 0).unwrap(),
+            cycle_day: utc("2024-03-10T00:00:00Z"),
+        };
+        let mut sched = Schedule {
+            schedule_type: ScheduleType::RandomWindow(window),
+            max_runs: None,
+            run_count: 1,
+            next_run: Some(utc("2024-03-10T02:00:00Z")),
+        };
+        // The next run is in tomorrow's window, not again in today's
+        let next = sched.advance_at(utc("2024-03-10T02:00:00Z")).unwrap();
+        assert!(next >= utc("2024-03-11T01:00:00Z") && next < utc("2024-03-11T04:00:00Z"));
+    }
+}
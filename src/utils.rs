@@ -1,2 +1,6 @@
 pub mod time;
-pub use time::*;
\ No newline at end of file
+pub mod spec;
+pub mod cron_builder;
+pub use time::*;
+pub use spec::*;
+pub use cron_builder::*;
\ No newline at end of file
@@ -0,0 +1,262 @@
+//! Module for building cron expressions from typed fields.
+//!
+//! `CronBuilder` produces the 7-field expression (`sec min hour day-of-month month
+//! day-of-week year`) accepted by `JobBuilder::cron`, so callers don't need to remember the
+//! field order. Unset fields match every value, except seconds which default to `0`.
+//! `validate_cron` checks hand-written expressions field by field.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use siafu::utils::cron_builder::{CronBuilder, Weekday};
+//!
+//! let expr = CronBuilder::new()
+//!     .at_hour(9)
+//!     .at_minute(0)
+//!     .on_weekdays(&[Weekday::Mon, Weekday::Fri])
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(expr, "0 0 9 * * Mon,Fri *");
+//! ```
+
+use thiserror::Error;
+
+pub use chrono::{Month, Weekday};
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CronBuilderError {
+    #[error("{field} value {value} out of range {min}..={max}")]
+    OutOfRange { field: &'static str, value: u32, min: u32, max: u32 },
+    #[error("expected 6 or 7 fields, found {0}")]
+    FieldCount(usize),
+    #[error("invalid {field} field '{part}': {reason}")]
+    InvalidField { field: &'static str, part: String, reason: &'static str },
+}
+
+// Name, bounds and whether names (e.g. `Jan`, `Mon`) are allowed, in expression order
+const CRON_FIELDS: [(&str, u32, u32, bool); 7] = [
+    ("second", 0, 59, false),
+    ("minute", 0, 59, false),
+    ("hour", 0, 23, false),
+    ("day of month", 1, 31, false),
+    ("month", 1, 12, true),
+    ("day of week", 1, 7, true),
+    ("year", 1970, 2100, false),
+];
+
+/// Check the syntax of every field of a 6 or 7-field cron expression.
+///
+/// Each comma-separated part must be `*`, `?`, a value or an `a-b` range, optionally
+/// followed by a `/step` of at least 1. Values must be within the field's bounds.
+pub fn validate_cron(expr: &str) -> Result<(), CronBuilderError> {
+    let fields: Vec<&str> = expr.split_whitespace().collect();
+    if !(6..=7).contains(&fields.len()) {
+        return Err(CronBuilderError::FieldCount(fields.len()));
+    }
+    for (value, &(field, min, max, names)) in fields.iter().zip(CRON_FIELDS.iter()) {
+        for part in value.split(',') {
+            validate_part(part, field, min, max, names)?;
+        }
+    }
+    Ok(())
+}
+
+fn validate_part(part: &str, field: &'static str, min: u32, max: u32, names: bool) -> Result<(), CronBuilderError> {
+    let invalid = |reason| CronBuilderError::InvalidField { field, part: part.to_string(), reason };
+    let (base, step) = match part.split_once('/') {
+        Some((base, step)) => (base, Some(step)),
+        None => (part, None),
+    };
+    if let Some(step) = step {
+        match step.parse::<u32>() {
+            Ok(0) => return Err(invalid("step must be at least 1")),
+            Ok(_) => {}
+            Err(_) => return Err(invalid("step must be a positive number")),
+        }
+    }
+    if base == "*" || base == "?" {
+        return Ok(());
+    }
+    let check = |value: &str| -> Result<Option<u32>, CronBuilderError> {
+        if names && !value.is_empty() && value.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Ok(None);
+        }
+        let value: u32 = value.parse().map_err(|_| invalid("expected a number"))?;
+        if !(min..=max).contains(&value) {
+            return Err(CronBuilderError::OutOfRange { field, value, min, max });
+        }
+        Ok(Some(value))
+    };
+    match base.split_once('-') {
+        Some((start, end)) => {
+            if let (Some(start), Some(end)) = (check(start)?, check(end)?)
+                && start > end
+            {
+                return Err(invalid("range start is after its end"));
+            }
+        }
+        None => {
+            check(base)?;
+        }
+    }
+    Ok(())
+}
+
+/// Builder for 7-field cron expressions.
+#[derive(Debug, Clone, Default)]
+pub struct CronBuilder {
+    seconds: Vec<u32>,
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    days_of_month: Vec<u32>,
+    months: Vec<Month>,
+    weekdays: Vec<Weekday>,
+}
+
+impl CronBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fire at the given second of the minute (0-59). May be called repeatedly.
+    pub fn at_second(mut self, second: u32) -> Self {
+        self.seconds.push(second);
+        self
+    }
+
+    /// Fire at the given minute of the hour (0-59). May be called repeatedly.
+    pub fn at_minute(mut self, minute: u32) -> Self {
+        self.minutes.push(minute);
+        self
+    }
+
+    /// Fire at the given hour of the day (0-23). May be called repeatedly.
+    pub fn at_hour(mut self, hour: u32) -> Self {
+        self.hours.push(hour);
+        self
+    }
+
+    /// Fire only on the given days of the month (1-31).
+    pub fn on_days_of_month(mut self, days: &[u32]) -> Self {
+        self.days_of_month.extend_from_slice(days);
+        self
+    }
+
+    /// Fire only in the given months.
+    pub fn in_months(mut self, months: &[Month]) -> Self {
+        self.months.extend_from_slice(months);
+        self
+    }
+
+    /// Fire only on the given days of the week.
+    pub fn on_weekdays(mut self, weekdays: &[Weekday]) -> Self {
+        self.weekdays.extend_from_slice(weekdays);
+        self
+    }
+
+    /// Validate the fields and produce the cron expression.
+    pub fn build(&self) -> Result<String, CronBuilderError> {
+        let seconds = if self.seconds.is_empty() {
+            "0".to_string()
+        } else {
+            numeric_field("second", &self.seconds, 0, 59)?
+        };
+        let fields = [
+            seconds,
+            numeric_field("minute", &self.minutes, 0, 59)?,
+            numeric_field("hour", &self.hours, 0, 23)?,
+            numeric_field("day of month", &self.days_of_month, 1, 31)?,
+            named_field(self.months.iter().map(|m| &m.name()[..3])),
+            named_field(self.weekdays.iter().map(|d| d.to_string())),
+            "*".to_string(),
+        ];
+        Ok(fields.join(" "))
+    }
+}
+
+// Comma-separated list of validated values, or `*` if empty
+fn numeric_field(field: &'static str, values: &[u32], min: u32, max: u32) -> Result<String, CronBuilderError> {
+    if values.is_empty() {
+        return Ok("*".to_string());
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    let mut parts = Vec::with_capacity(sorted.len());
+    for value in sorted {
+        if !(min..=max).contains(&value) {
+            return Err(CronBuilderError::OutOfRange { field, value, min, max });
+        }
+        parts.push(value.to_string());
+    }
+    Ok(parts.join(","))
+}
+
+// Comma-separated list of names, or `*` if empty
+fn named_field<S: ToString>(names: impl Iterator<Item = S>) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for name in names {
+        let name = name.to_string();
+        if !parts.contains(&name) {
+            parts.push(name);
+        }
+    }
+    if parts.is_empty() { "*".to_string() } else { parts.join(",") }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cron::Schedule as CronSchedule;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_build_defaults() {
+        assert_eq!(CronBuilder::new().build().unwrap(), "0 * * * * * *");
+    }
+
+    #[test]
+    fn test_build_full_expression_parses() {
+        let expr = CronBuilder::new()
+            .at_second(30)
+            .at_minute(15)
+            .at_hour(17)
+            .at_hour(9)
+            .on_days_of_month(&[1, 15])
+            .in_months(&[Month::January, Month::July])
+            .on_weekdays(&[Weekday::Mon, Weekday::Sun])
+            .build()
+            .unwrap();
+        assert_eq!(expr, "30 15 9,17 1,15 Jan,Jul Mon,Sun *");
+        assert!(CronSchedule::from_str(&expr).is_ok());
+    }
+
+    #[test]
+    fn test_validate_cron() {
+        assert!(validate_cron("*/5 * * * * * *").is_ok());
+        assert!(validate_cron("0 0 9-17 * * Mon-Fri *").is_ok());
+        assert!(validate_cron("0 0/15 9-17/2 1,15 Jan-Jun ? 2030").is_ok());
+        assert!(validate_cron("0 0 9 * *").is_err());
+        assert!(matches!(
+            validate_cron("*/0 * * * * *"),
+            Err(CronBuilderError::InvalidField { field: "second", .. })
+        ));
+        assert!(matches!(
+            validate_cron("0 0 17-9 * * *"),
+            Err(CronBuilderError::InvalidField { field: "hour", .. })
+        ));
+        assert!(matches!(
+            validate_cron("0 61 * * * *"),
+            Err(CronBuilderError::OutOfRange { field: "minute", value: 61, .. })
+        ));
+        assert!(validate_cron("0 0 noon * * *").is_err());
+    }
+
+    #[test]
+    fn test_build_rejects_out_of_range() {
+        let err = CronBuilder::new().at_hour(24).build().unwrap_err();
+        assert_eq!(err, CronBuilderError::OutOfRange { field: "hour", value: 24, min: 0, max: 23 });
+        assert!(CronBuilder::new().at_minute(60).build().is_err());
+        assert!(CronBuilder::new().on_days_of_month(&[0]).build().is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! Module for parsing complete schedule specifications from text.
+//!
+//! `ScheduleSpec` describes a whole schedule, including recurrence, so jobs can be configured
+//! entirely from strings. The kind of schedule is given by a tag before the first `:`.
+//!
+//! - `once:<schedule time>`, e.g. `once:delay:5s` or `once:at:2025-05-05T12:00:00Z`
+//! - `every:<duration>`, e.g. `every:5m`
+//! - `cron:<expression>`, e.g. `cron:0 0 * * * * *`
+//! - `random:<schedule time>..<schedule time>`, e.g. `random:delay:5s..delay:15s`
+//!
+//! # Examples
+//!
+//! ```rust
+//! use siafu::utils::spec::ScheduleSpec;
+//! use std::str::FromStr;
+//!
+//! let spec = ScheduleSpec::from_str("every:5m").unwrap();
+//! let spec = ScheduleSpec::try_from("random:delay:5s..delay:15s").unwrap();
+//! ```
+
+use std::{str::FromStr, time::Duration};
+use cron::Schedule as CronSchedule;
+use humantime::parse_duration;
+use thiserror::Error;
+
+use super::time::{ScheduleTime, ScheduleTimeError};
+
+/// A complete schedule parsed from text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleSpec {
+    /// Run once at the given time.
+    Once(ScheduleTime),
+    /// Run repeatedly with the given interval.
+    Every(Duration),
+    /// Run whenever the cron expression matches.
+    Cron(CronSchedule),
+    /// Run once at a random time within the window.
+    Random(ScheduleTime, ScheduleTime),
+}
+
+#[derive(Debug, Error)]
+pub enum ScheduleSpecError {
+    #[error("Invalid format: expected '<kind>:<value>'")]
+    InvalidFormat,
+    #[error("Unknown schedule kind: '{0}'")]
+    UnknownKind(String),
+    #[error("Invalid random window: expected '<start>..<end>'")]
+    InvalidRandomWindow,
+    #[error("Invalid schedule time: {0}")]
+    ScheduleTimeError(#[from] ScheduleTimeError),
+    #[error("Failed to parse duration: {0}")]
+    DurationParseError(#[from] humantime::DurationError),
+    #[error("Failed to parse cron expression: {0}")]
+    CronParseError(#[from] cron::error::Error),
+}
+
+impl FromStr for ScheduleSpec {
+    type Err = ScheduleSpecError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = s.split_once(':').ok_or(ScheduleSpecError::InvalidFormat)?;
+        let kind = kind.trim().to_lowercase();
+        let value = value.trim();
+
+        match kind.as_str() {
+            "once" => Ok(ScheduleSpec::Once(value.parse()?)),
+            "every" => Ok(ScheduleSpec::Every(parse_duration(value)?)),
+            "cron" => Ok(ScheduleSpec::Cron(CronSchedule::from_str(value)?)),
+            "random" => {
+                let (start, end) = value.split_once("..").ok_or(ScheduleSpecError::InvalidRandomWindow)?;
+                Ok(ScheduleSpec::Random(start.parse()?, end.parse()?))
+            }
+            _ => Err(ScheduleSpecError::UnknownKind(kind)),
+        }
+    }
+}
+
+impl TryFrom<&str> for ScheduleSpec {
+    type Error = ScheduleSpecError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_once() {
+        let spec: ScheduleSpec = "once:delay:5s".parse().unwrap();
+        assert_eq!(spec, ScheduleSpec::Once(ScheduleTime::Delay(Duration::from_secs(5))));
+    }
+
+    #[test]
+    fn test_parse_every() {
+        let spec: ScheduleSpec = "every:5m".parse().unwrap();
+        assert_eq!(spec, ScheduleSpec::Every(Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn test_parse_cron() {
+        let spec: ScheduleSpec = "cron:0 0 * * * * *".parse().unwrap();
+        assert!(matches!(spec, ScheduleSpec::Cron(_)));
+    }
+
+    #[test]
+    fn test_parse_random() {
+        let spec = ScheduleSpec::try_from("random:delay:5s..delay:15s").unwrap();
+        assert_eq!(spec, ScheduleSpec::Random(
+            ScheduleTime::Delay(Duration::from_secs(5)),
+            ScheduleTime::Delay(Duration::from_secs(15)),
+        ));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!("every".parse::<ScheduleSpec>(), Err(ScheduleSpecError::InvalidFormat)));
+        assert!(matches!("sometimes:5m".parse::<ScheduleSpec>(), Err(ScheduleSpecError::UnknownKind(_))));
+        assert!(matches!("random:delay:5s".parse::<ScheduleSpec>(), Err(ScheduleSpecError::InvalidRandomWindow)));
+        assert!(matches!("every:soon".parse::<ScheduleSpec>(), Err(ScheduleSpecError::DurationParseError(_))));
+        assert!(matches!("cron:not a cron".parse::<ScheduleSpec>(), Err(ScheduleSpecError::CronParseError(_))));
+        assert!(matches!("once:later".parse::<ScheduleSpec>(), Err(ScheduleSpecError::ScheduleTimeError(_))));
+    }
+}
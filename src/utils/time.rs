@@ -15,8 +15,9 @@
 //! let at = ScheduleTime::from_str("at:2025-05-05T12:00:00Z").unwrap();
 //! ```
 
-use std::{str::FromStr, time::{Duration, SystemTime}};
-use humantime::{format_duration, format_rfc3339, parse_duration, Timestamp};
+use std::{ops::{Add, Sub}, str::FromStr, time::{Duration, SystemTime, UNIX_EPOCH}};
+use humantime::{format_duration, format_rfc3339_nanos, format_rfc3339_seconds, parse_duration, Timestamp};
+use rand::Rng;
 use thiserror::Error;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -38,12 +39,29 @@ pub enum ScheduleTimeError {
     DurationParseError(#[from] humantime::DurationError),
     #[error("Failed to parse timestamp: {0}")]
     TimestampParseError(#[from] humantime::TimestampError),
+    #[error("Unix time out of range: {0}")]
+    OutOfRange(i64),
 }
 
+/// Longest string `ScheduleTime::from_str` will parse, in bytes.
+pub const DEFAULT_MAX_SCHEDULE_TIME_LEN: usize = 256;
+
 impl FromStr for ScheduleTime {
     type Err = ScheduleTimeError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ScheduleTime::parse_with_limit(s, DEFAULT_MAX_SCHEDULE_TIME_LEN)
+    }
+}
+
+impl ScheduleTime {
+    /// Parse like `from_str`, rejecting input longer than `max_len` bytes with `InvalidFormat`.
+    ///
+    /// Bounds the work done on untrusted configuration before it reaches `humantime`.
+    pub fn parse_with_limit(s: &str, max_len: usize) -> Result<Self, ScheduleTimeError> {
+        if s.len() > max_len {
+            return Err(ScheduleTimeError::InvalidFormat);
+        }
         let parts: Vec<&str> = s.splitn(2, ':').collect();
         if parts.len() != 2 {
             return Err(ScheduleTimeError::InvalidFormat);
@@ -68,6 +86,155 @@ impl FromStr for ScheduleTime {
     }
 }
 
+impl ScheduleTime {
+    /// Resolve to an absolute time, measuring delays from `now`.
+    ///
+    /// Returns `None` if the delay is too large to represent as a `SystemTime`.
+    pub fn resolve(&self, now: SystemTime) -> Option<SystemTime> {
+        match self {
+            ScheduleTime::At(time) => Some(*time),
+            ScheduleTime::Delay(delay) => now.checked_add(*delay),
+        }
+    }
+
+    /// An absolute time `secs` seconds from the Unix epoch; negative values are before it.
+    ///
+    /// Returns `OutOfRange` if the time cannot be represented as a `SystemTime`.
+    pub fn from_unix(secs: i64) -> Result<ScheduleTime, ScheduleTimeError> {
+        let offset = Duration::from_secs(secs.unsigned_abs());
+        let time = if secs >= 0 {
+            UNIX_EPOCH.checked_add(offset)
+        } else {
+            UNIX_EPOCH.checked_sub(offset)
+        };
+        time.map(ScheduleTime::At).ok_or(ScheduleTimeError::OutOfRange(secs))
+    }
+
+    /// Whole seconds from the Unix epoch to an `At` time, rounded down.
+    ///
+    /// Returns `None` for a `Delay`, which has no fixed point in time, or if the time does
+    /// not fit in an `i64`.
+    pub fn to_unix(&self) -> Option<i64> {
+        let ScheduleTime::At(time) = self else {
+            return None;
+        };
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => i64::try_from(since.as_secs()).ok(),
+            Err(err) => {
+                let before = err.duration();
+                // Round towards negative infinity, so a fractional second before the epoch is -1
+                let secs = before.as_secs() + u64::from(before.subsec_nanos() > 0);
+                i64::try_from(secs).ok().map(|secs| -secs)
+            }
+        }
+    }
+
+    /// Format like `Display`, but with nanosecond precision for `At` times.
+    pub fn to_string_nanos(&self) -> String {
+        match self {
+            ScheduleTime::At(system_time) => format!("at:{}", format_rfc3339_nanos(*system_time)),
+            ScheduleTime::Delay(_) => self.to_string(),
+        }
+    }
+
+    /// Whether two schedule points are within `tolerance` of each other.
+    ///
+    /// Delays are measured from the same instant, so a delay can be compared with an
+    /// absolute time. Points that cannot be represented never match.
+    pub fn approx_eq(&self, other: &Self, tolerance: Duration) -> bool {
+        let now = SystemTime::now();
+        match (self.resolve(now), other.resolve(now)) {
+            (Some(a), Some(b)) => {
+                let diff = a.duration_since(b).or_else(|_| b.duration_since(a)).unwrap_or_default();
+                diff <= tolerance
+            }
+            _ => false,
+        }
+    }
+}
+
+impl TryFrom<i64> for ScheduleTime {
+    type Error = ScheduleTimeError;
+
+    /// An absolute time from Unix epoch seconds, as with `ScheduleTime::from_unix`.
+    fn try_from(secs: i64) -> Result<Self, Self::Error> {
+        ScheduleTime::from_unix(secs)
+    }
+}
+
+impl From<Timestamp> for ScheduleTime {
+    fn from(timestamp: Timestamp) -> Self {
+        ScheduleTime::At(timestamp.into())
+    }
+}
+
+impl ScheduleTime {
+    /// Shift later by `delta`, or `None` if the result cannot be represented.
+    pub fn checked_add(&self, delta: Duration) -> Option<ScheduleTime> {
+        match self {
+            ScheduleTime::Delay(delay) => delay.checked_add(delta).map(ScheduleTime::Delay),
+            ScheduleTime::At(time) => time.checked_add(delta).map(ScheduleTime::At),
+        }
+    }
+
+    /// Shift earlier by `delta`, or `None` if the result cannot be represented.
+    pub fn checked_sub(&self, delta: Duration) -> Option<ScheduleTime> {
+        match self {
+            ScheduleTime::Delay(delay) => delay.checked_sub(delta).map(ScheduleTime::Delay),
+            ScheduleTime::At(time) => time.checked_sub(delta).map(ScheduleTime::At),
+        }
+    }
+}
+
+impl Add<Duration> for ScheduleTime {
+    type Output = ScheduleTime;
+
+    /// Shift later by `delta`: a longer `Delay`, or a later `At`.
+    ///
+    /// A `Delay` saturates at `Duration::MAX`, and an `At` at the latest time the platform
+    /// can represent; use `checked_add` to detect this.
+    fn add(self, delta: Duration) -> ScheduleTime {
+        match self {
+            ScheduleTime::Delay(delay) => ScheduleTime::Delay(delay.saturating_add(delta)),
+            ScheduleTime::At(time) => ScheduleTime::At(saturating_shift(time, delta, SystemTime::checked_add)),
+        }
+    }
+}
+
+impl Sub<Duration> for ScheduleTime {
+    type Output = ScheduleTime;
+
+    /// Shift earlier by `delta`: a shorter `Delay`, or an earlier `At`.
+    ///
+    /// A `Delay` saturates at zero, i.e. run immediately, and an `At` at the earliest time the
+    /// platform can represent; use `checked_sub` to detect this.
+    fn sub(self, delta: Duration) -> ScheduleTime {
+        match self {
+            ScheduleTime::Delay(delay) => ScheduleTime::Delay(delay.saturating_sub(delta)),
+            ScheduleTime::At(time) => ScheduleTime::At(saturating_shift(time, delta, SystemTime::checked_sub)),
+        }
+    }
+}
+
+// Shift `time` by `delta` with `shift`, or as far as can be represented. The range of
+// `SystemTime` is platform specific, so the furthest shift is found by bisection.
+fn saturating_shift(time: SystemTime, delta: Duration, shift: fn(&SystemTime, Duration) -> Option<SystemTime>) -> SystemTime {
+    if let Some(shifted) = shift(&time, delta) {
+        return shifted;
+    }
+    // `shift` succeeds for `fits` and fails for `overflows`
+    let (mut fits, mut overflows) = (Duration::ZERO, delta);
+    while overflows - fits > Duration::from_nanos(1) {
+        let mid = fits + (overflows - fits) / 2;
+        if shift(&time, mid).is_some() {
+            fits = mid;
+        } else {
+            overflows = mid;
+        }
+    }
+    shift(&time, fits).unwrap_or(time)
+}
+
 use std::fmt;
 
 impl fmt::Display for ScheduleTime {
@@ -77,23 +244,56 @@ impl fmt::Display for ScheduleTime {
                 write!(f, "delay:{}", format_duration(*duration))
             }
             ScheduleTime::At(system_time) => {
-                 // Use humantime's formatter for SystemTime to RFC3339
-                write!(f, "at:{}", format_rfc3339(*system_time))
+                // Whole seconds only, so parsing the output and displaying it again is stable
+                write!(f, "at:{}", format_rfc3339_seconds(*system_time))
             }
         }
     }
 }
 
+// Pick a uniformly random time in `[start, end)`, or exactly `start` for a zero-width window.
+// Sampling is done over `u128` nanoseconds so windows longer than `u64::MAX` nanoseconds
+// (~584 years) are not truncated.
+pub(crate) fn random_time_between<R: Rng + ?Sized>(rng: &mut R, start: SystemTime, end: SystemTime) -> Option<SystemTime> {
+    let range = end.duration_since(start).ok()?;
+    let nanos = range.as_nanos();
+    if nanos == 0 {
+        return Some(start);
+    }
+    let offset = rng.random_range(0..nanos);
+    let offset = Duration::new((offset / 1_000_000_000) as u64, (offset % 1_000_000_000) as u32);
+    start.checked_add(offset)
+}
+
 #[cfg(test)]
 mod tests{
 
     use super::*;
     use std::time::Duration;
 
+    #[test]
+    fn test_approx_eq() {
+        let now = SystemTime::now();
+        let tolerance = Duration::from_millis(100);
+        let at = ScheduleTime::At(now);
+        assert!(at.approx_eq(&ScheduleTime::At(now + Duration::from_millis(50)), tolerance));
+        assert!(at.approx_eq(&ScheduleTime::At(now - Duration::from_millis(100)), tolerance));
+        assert!(!at.approx_eq(&ScheduleTime::At(now + Duration::from_millis(150)), tolerance));
+
+        let delay = ScheduleTime::Delay(Duration::from_secs(5));
+        assert!(delay.approx_eq(&ScheduleTime::Delay(Duration::from_millis(5050)), tolerance));
+        assert!(!delay.approx_eq(&ScheduleTime::Delay(Duration::from_secs(6)), tolerance));
+
+        // A delay resolves relative to now, so it matches the equivalent absolute time
+        let later = ScheduleTime::At(SystemTime::now() + Duration::from_secs(5));
+        assert!(delay.approx_eq(&later, Duration::from_secs(1)));
+        assert!(!ScheduleTime::Delay(Duration::from_secs(u64::MAX)).approx_eq(&later, tolerance));
+    }
+
     #[test]
     fn test_at(){
         let system_time = SystemTime::now();
-        let schedule_time = ScheduleTime::At(system_time.clone());
+        let schedule_time = ScheduleTime::At(system_time);
         if let ScheduleTime::At(inner_time) = schedule_time {
             assert_eq!(system_time, inner_time);
         } else {
@@ -129,6 +329,44 @@ mod tests{
         }
     }
 
+    // Test that an arbitrary At time survives parse -> display -> parse
+    #[test]
+    fn test_round_trip_at_now() {
+        let now = ScheduleTime::At(SystemTime::now());
+        let displayed = now.to_string();
+        let parsed: ScheduleTime = displayed.parse().unwrap();
+        assert_eq!(parsed.to_string(), displayed);
+        assert_eq!(parsed.to_string().parse::<ScheduleTime>().unwrap(), parsed);
+        // Display drops the fraction; the nanos variant keeps it
+        assert!(now.approx_eq(&parsed, Duration::from_secs(1)));
+        let precise: ScheduleTime = now.to_string_nanos().parse().unwrap();
+        assert_eq!(precise, now);
+    }
+
+    #[test]
+    fn test_unix_conversions() {
+        let at = ScheduleTime::from_unix(1_577_882_096).unwrap();
+        assert_eq!(at, "at:2020-01-01T12:34:56Z".parse().unwrap());
+        assert_eq!(at.to_unix(), Some(1_577_882_096));
+        assert_eq!(ScheduleTime::try_from(1_577_882_096i64).unwrap(), at);
+        // The range of `SystemTime` is platform specific, but the extremes never panic
+        for secs in [i64::MAX, i64::MIN] {
+            if let Err(err) = ScheduleTime::try_from(secs) {
+                assert!(matches!(err, ScheduleTimeError::OutOfRange(s) if s == secs));
+            }
+        }
+
+        let before_epoch = ScheduleTime::from_unix(-86400).unwrap();
+        assert_eq!(before_epoch, ScheduleTime::At(UNIX_EPOCH - Duration::from_secs(86400)));
+        assert_eq!(before_epoch.to_unix(), Some(-86400));
+        assert_eq!(ScheduleTime::At(UNIX_EPOCH - Duration::from_millis(1)).to_unix(), Some(-1));
+        assert_eq!(ScheduleTime::At(UNIX_EPOCH + Duration::from_millis(1500)).to_unix(), Some(1));
+
+        assert_eq!(ScheduleTime::Delay(Duration::from_secs(5)).to_unix(), None);
+        let timestamp: Timestamp = "2020-01-01T12:34:56Z".parse().unwrap();
+        assert_eq!(ScheduleTime::from(timestamp), at);
+    }
+
     // Test that formatting a Delay round-trips back to the same string
     #[test]
     fn test_round_trip_delay() {
@@ -161,4 +399,35 @@ mod tests{
         let err = "at:abc".parse::<ScheduleTime>().unwrap_err();
         assert!(matches!(err, ScheduleTimeError::TimestampParseError(_)));
     }
+
+    #[test]
+    fn test_error_too_long() {
+        let long = format!("delay:{}", "1s ".repeat(1000));
+        assert!(matches!(long.parse::<ScheduleTime>(), Err(ScheduleTimeError::InvalidFormat)));
+        // A higher limit lets it through
+        assert_eq!(ScheduleTime::parse_with_limit(&long, long.len()).unwrap(), ScheduleTime::Delay(Duration::from_secs(1000)));
+        assert!(matches!(ScheduleTime::parse_with_limit("delay:10s", 8), Err(ScheduleTimeError::InvalidFormat)));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let delay = ScheduleTime::Delay(Duration::from_secs(30));
+        assert_eq!(delay.clone() + Duration::from_secs(15), ScheduleTime::Delay(Duration::from_secs(45)));
+        assert_eq!(delay.clone() - Duration::from_secs(10), ScheduleTime::Delay(Duration::from_secs(20)));
+        // Shortening a delay past zero runs immediately
+        assert_eq!(delay.clone() - Duration::from_secs(60), ScheduleTime::Delay(Duration::ZERO));
+        assert_eq!(delay.checked_sub(Duration::from_secs(60)), None);
+
+        let at = ScheduleTime::from_unix(1_000).unwrap();
+        assert_eq!(at.clone() + Duration::from_secs(15), ScheduleTime::from_unix(1_015).unwrap());
+        assert_eq!(at.clone() - Duration::from_secs(10), ScheduleTime::from_unix(990).unwrap());
+        // Absolute times may go before the epoch
+        assert_eq!(at.clone() - Duration::from_secs(1_500), ScheduleTime::from_unix(-500).unwrap());
+        assert_eq!(at.checked_add(Duration::MAX), None);
+        // Times that cannot be represented saturate instead of panicking
+        let latest = at.clone() + Duration::MAX;
+        assert_eq!(latest.checked_add(Duration::from_nanos(1)), None);
+        let earliest = at - Duration::MAX;
+        assert_eq!(earliest.checked_sub(Duration::from_nanos(1)), None);
+    }
 }
\ No newline at end of file